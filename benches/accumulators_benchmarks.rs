@@ -91,6 +91,153 @@ mod rsa_benches {
     }
 }
 
+mod fixed_base_benches {
+    use super::*;
+    use accumulators::group::RSAGroup;
+    use accumulators::math::{FixedBaseTable, MontgomeryContext, DEFAULT_WINDOW};
+    use accumulators::traits::PrimeGroup;
+    use num_bigint::{BigUint, RandBigInt};
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    /// Exponent size, about the size of a set with 16 elements.
+    const E: usize = 16 * 256;
+
+    fn setup(bits: usize) -> (BigUint, BigUint, BigUint) {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let (n, g) = RSAGroup::generate_primes(rng, bits).unwrap();
+        let e = rng.gen_biguint(E);
+
+        (n, g, e)
+    }
+
+    fn bench_modpow(c: &mut Criterion) {
+        for &bits in &[2048, 3072] {
+            let (n, g, e) = setup(bits);
+
+            c.bench_function(&format!("bench_modpow_{}", bits), move |b| {
+                b.iter(|| g.modpow(&e, &n))
+            });
+        }
+    }
+
+    fn bench_fixed_base_modpow(c: &mut Criterion) {
+        for &bits in &[2048, 3072] {
+            let (n, g, e) = setup(bits);
+            let table = FixedBaseTable::new(&g, &n, DEFAULT_WINDOW, E);
+
+            c.bench_function(&format!("bench_fixed_base_modpow_{}", bits), move |b| {
+                b.iter(|| table.modpow(&e, &n))
+            });
+        }
+    }
+
+    /// Number of sequential squarings.
+    const SQUARINGS: u64 = 1000;
+
+    fn bench_square(c: &mut Criterion) {
+        for &bits in &[2048, 3072] {
+            let (n, g, _) = setup(bits);
+
+            c.bench_function(&format!("bench_square_{}", bits), move |b| {
+                b.iter(|| (0..SQUARINGS).fold(g.clone(), |x, _| (&x * &x) % &n))
+            });
+        }
+    }
+
+    fn bench_montgomery_square(c: &mut Criterion) {
+        for &bits in &[2048, 3072] {
+            let (n, g, _) = setup(bits);
+            let ctx = MontgomeryContext::new(&n);
+            let g = ctx.to_repr(&g);
+
+            c.bench_function(&format!("bench_montgomery_square_{}", bits), move |b| {
+                b.iter(|| ctx.repeated_square(&g, SQUARINGS))
+            });
+        }
+    }
+
+    criterion_group! {
+        name = fixed_base_benches;
+        config = Criterion::default();
+        targets =
+            bench_modpow,
+            bench_fixed_base_modpow,
+            bench_square,
+            bench_montgomery_square,
+    }
+}
+
+mod vdf_benches {
+    use super::*;
+    use accumulators::group::RSAGroup;
+    use accumulators::traits::PrimeGroup;
+    use accumulators::vdf::{self, pietrzak};
+    use num_bigint::{BigUint, RandBigInt};
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    const N: usize = 2048;
+    const T: u64 = 1 << 12;
+
+    fn setup() -> (BigUint, BigUint, BigUint) {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let (n, _) = RSAGroup::generate_primes(rng, N).unwrap();
+        let x = rng.gen_biguint(N) % &n;
+        let y = vdf::eval(&x, T, &n);
+
+        (n, x, y)
+    }
+
+    fn bench_wesolowski_prove(c: &mut Criterion) {
+        let (n, x, y) = setup();
+
+        c.bench_function("bench_wesolowski_prove", move |b| {
+            b.iter(|| vdf::prove(&x, &y, T, &n))
+        });
+    }
+
+    fn bench_wesolowski_verify(c: &mut Criterion) {
+        let (n, x, y) = setup();
+        let pi = vdf::prove(&x, &y, T, &n);
+
+        c.bench_function("bench_wesolowski_verify", move |b| {
+            b.iter(|| vdf::verify(&x, &y, T, &pi, &n))
+        });
+    }
+
+    fn bench_pietrzak_prove(c: &mut Criterion) {
+        let (n, x, _) = setup();
+        let (y, checkpoints) = pietrzak::eval(&x, T, &n);
+
+        c.bench_function("bench_pietrzak_prove", move |b| {
+            b.iter(|| pietrzak::prove(&x, &y, T, &checkpoints, &n))
+        });
+    }
+
+    fn bench_pietrzak_verify(c: &mut Criterion) {
+        let (n, x, _) = setup();
+        let (y, checkpoints) = pietrzak::eval(&x, T, &n);
+        let pi = pietrzak::prove(&x, &y, T, &checkpoints, &n);
+
+        c.bench_function("bench_pietrzak_verify", move |b| {
+            b.iter(|| pietrzak::verify(&x, &y, T, &pi, &n))
+        });
+    }
+
+    criterion_group! {
+        name = vdf_benches;
+        config = Criterion::default();
+        targets =
+            bench_wesolowski_prove,
+            bench_wesolowski_verify,
+            bench_pietrzak_prove,
+            bench_pietrzak_verify,
+    }
+}
+
 #[cfg(not(feature = "classgroup"))]
 mod classgroup_benches {
     use super::*;
@@ -149,5 +296,7 @@ mod classgroup_benches {
 
 criterion_main!(
     rsa_benches::rsa_benches,
+    fixed_base_benches::fixed_base_benches,
+    vdf_benches::vdf_benches,
     classgroup_benches::classgroup_benches
 );
@@ -1,20 +1,38 @@
-use num_bigint::traits::ModInverse;
-use num_bigint::{BigInt, BigUint, ExtendedGcd, IntoBigUint};
+use std::sync::Arc;
+
+use num_bigint::{BigInt, BigUint};
 use num_integer::Integer;
-use num_traits::{One, Zero};
+use num_traits::{One, Signed, Zero};
 use rand::CryptoRng;
 use rand::Rng;
 
-use crate::math::{modpow_uint_int, root_factor, shamir_trick};
-use crate::proofs;
+use crate::math::backend::{self, extended_gcd, mod_inverse};
+use crate::math::{
+    multi_modpow, multi_modpow_int, product, root_factor, root_factor_fixed_base,
+    secret_mod_inverse, secret_modpow_int, shamir_trick, FixedBaseTable, DEFAULT_WINDOW,
+};
+use crate::proofs::{self, KnowledgeProof};
+use crate::set::ChunkedSet;
 use crate::traits::*;
 
+/// Default maximum number of exponent bits covered by the precomputed powers of the generator,
+/// see [GenericAccumulator::set_fixed_base_max_bits].
+///
+/// The table stores one group element per `DEFAULT_WINDOW` bits, so for a 2048 bit modulus this
+/// is about 2.8 MB, covering sets of about 256 primes of 256 bits.
+pub const FIXED_BASE_MAX_BITS: usize = 1 << 16;
+
+#[cfg(feature = "serde")]
+fn default_fixed_base_max_bits() -> usize {
+    FIXED_BASE_MAX_BITS
+}
+
 // All accumulated values are small odd primes.
 // Arbitrary data values can be hashed to small primes,
 // It is also assumed that no item is added twice to the accumulator !!!
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone)]
-pub struct Accumulator {
+pub struct GenericAccumulator<S> {
     /// Length of the Integer we use in bits, This is Lambda and our security parameter
     int_size_bits: usize, //change this to u16
 
@@ -30,17 +48,204 @@ pub struct Accumulator {
     root: BigUint,
 
     /// The set of elements currently accumulated (product of the current set)
-    set: BigUint,
+    set: S,
+
+    /// Precomputed powers of the generator, extended as the set grows.
+    /// Shared between clones, until one of them extends it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    g_table: Arc<FixedBaseTable>,
+
+    /// Maximum number of exponent bits covered by `g_table`.
+    #[cfg_attr(feature = "serde", serde(default = "default_fixed_base_max_bits"))]
+    g_table_max_bits: usize,
 }
 
-impl Accumulator {}
+/// An accumulator, storing the product of its elements in a single integer.
+pub type Accumulator = GenericAccumulator<BigUint>;
+
+/// An accumulator, storing the product of its elements in chunks, see [ChunkedSet].
+pub type ChunkedAccumulator = GenericAccumulator<ChunkedSet>;
+
+impl<S: AccumulatedSet> GenericAccumulator<S> {
+    /// Calculates `g^e`, using the precomputed powers of `g` if possible.
+    fn g_pow(&self, e: &BigUint) -> BigUint {
+        self.g_table
+            .modpow(e, &self.n)
+            .unwrap_or_else(|| backend::modpow(&self.g, e, &self.n))
+    }
+
+    /// Calculates `g^e`, for a signed exponent `e`.
+    /// Returns `None` if `e` is negative and `g` is not invertible.
+    fn g_pow_int(&self, e: &BigInt) -> Option<BigUint> {
+        let g_e = self.g_pow(&e.abs().to_biguint().unwrap());
+
+        if e.is_negative() {
+            mod_inverse(&g_e, &self.n)
+        } else {
+            Some(g_e)
+        }
+    }
+
+    /// Returns `(a, b)`, such that `a x + b s* = 1`, or `None` if `x` is not coprime to `s*`.
+    ///
+    /// Only `s* mod x` takes part in the extended gcd, `a` is recovered by an exact division,
+    /// which is much cheaper than an extended gcd with `s*` for large sets. Both `a` and `g^a`
+    /// are still linear in the size of the set.
+    fn bezout(&self, x: &BigUint) -> Option<(BigInt, BigInt)> {
+        // a' x + b (s* mod x) = 1
+        let (gcd, _, b) = extended_gcd(x, &self.set.residue(x));
+        if !gcd.is_one() {
+            return None;
+        }
+
+        // a <- (1 - b s*) / x
+        let s_star = BigInt::from(self.set.product().into_owned());
+        let a = (BigInt::one() - &b * s_star) / BigInt::from(x.clone());
+
+        Some((a, b))
+    }
+
+    /// Extends the precomputed powers of `g` to cover the current set.
+    fn extend_g_table(&mut self) {
+        let bits = std::cmp::min(self.set.bits(), self.g_table_max_bits);
+        if self.g_table.bits() < bits {
+            Arc::make_mut(&mut self.g_table).extend(&self.g, &self.n, bits);
+        }
+    }
+
+    /// Sets the maximum number of exponent bits covered by the precomputed powers of `g`,
+    /// [FIXED_BASE_MAX_BITS] by default. Exponentiations of `g` with larger exponents fall back
+    /// to `modpow`. Lowering it drops the powers above the new limit.
+    pub fn set_fixed_base_max_bits(&mut self, bits: usize) {
+        self.g_table_max_bits = bits;
+        if self.g_table.bits() > bits {
+            Arc::make_mut(&mut self.g_table).truncate(bits);
+        }
+        self.extend_g_table();
+    }
+
+    /// Proves that every element accumulated in `other` is also accumulated in `self`.
+    /// Both accumulators must share the same group and generator.
+    /// Returns `None` if the groups differ or `other` is not a subset.
+    ///
+    /// With `s* = k * o*` this is a NI-PoKE2 for `k`, such that `other^k = A`.
+    pub fn prove_subset(&self, other: &Self) -> Option<KnowledgeProof> {
+        if self.g != other.g || self.n != other.n {
+            return None;
+        }
+
+        // k <- s* / o*
+        let (k, r) = self.set.product().div_rem(&other.set.product());
+        if !r.is_zero() {
+            return None;
+        }
+
+        // pi <- NI-PoKE2(k, other, A)
+        Some(proofs::ni_poke2_prove(k, &other.root, &self.root, &self.n))
+    }
+
+    /// Verifies that the accumulator with state `other` is a subset of `self`.
+    pub fn ver_subset(&self, other: &BigUint, pi: &KnowledgeProof) -> bool {
+        proofs::ni_poke2_verify(other, &self.root, pi, &self.n)
+    }
+
+    /// Verifies many membership witnesses with a NI-PoE at once, as created by [mem_wit_create_star].
+    /// Returns the index of the first invalid proof, if the batch is rejected.
+    /// If the lengths differ, this is the index of the first element or proof without a partner.
+    pub fn ver_mem_star_batch(
+        &self,
+        xs: &[BigUint],
+        pis: &[(BigUint, BigUint)],
+    ) -> Result<(), usize> {
+        if xs.len() != pis.len() {
+            return Err(std::cmp::min(xs.len(), pis.len()));
+        }
+
+        let instances = xs
+            .iter()
+            .zip(pis)
+            .map(|(x, (w_x, q))| (x, w_x, &self.root, q))
+            .collect::<Vec<_>>();
+
+        proofs::ni_poe_batch_verify(&instances, &self.n)
+    }
+
+    /// Proves that `self` and `other` have no element in common.
+    /// Both accumulators must share the same group and generator.
+    /// Returns `None` if the groups differ or the sets intersect.
+    ///
+    /// With `a s* + b o* = 1` we have `A^a other^b = g`, so the proof is
+    /// `v = A^a` together with a NI-PoKE2 for `a` and one for `b`.
+    pub fn prove_disjoint(
+        &self,
+        other: &Self,
+    ) -> Option<(BigUint, KnowledgeProof, KnowledgeProof)> {
+        if self.g != other.g || self.n != other.n {
+            return None;
+        }
+
+        let g = &self.g;
+        let n = &self.n;
+
+        // a, b <- Bezout(s*, o*)
+        let (gcd, a, b) = extended_gcd(&self.set.product(), &other.set.product());
+        if !gcd.is_one() {
+            return None;
+        }
 
-impl StaticAccumulator for Accumulator {
+        // v <- A^a
+        let v = secret_modpow_int(&self.root, &a, n).expect("invalid state");
+        // k <- g * v^-1
+        let k = (g * secret_mod_inverse(&v, n).expect("invalid state")) % n;
+
+        // pi_a <- NI-PoKE2(a, A, v)
+        let pi_a = proofs::ni_poke2_prove(a, &self.root, &v, n);
+        // pi_b <- NI-PoKE2(b, other, g * v^-1)
+        let pi_b = proofs::ni_poke2_prove(b, &other.root, &k, n);
+
+        Some((v, pi_a, pi_b))
+    }
+
+    /// Verifies that the accumulator with state `other` is disjoint from `self`.
+    pub fn ver_disjoint(
+        &self,
+        other: &BigUint,
+        pi: &(BigUint, KnowledgeProof, KnowledgeProof),
+    ) -> bool {
+        let g = &self.g;
+        let n = &self.n;
+
+        let (v, pi_a, pi_b) = pi;
+
+        // verify NI-PoKE2 for a
+        if !proofs::ni_poke2_verify(&self.root, v, pi_a, n) {
+            return false;
+        }
+
+        // verify NI-PoKE2 for b
+        let k = match mod_inverse(v, n) {
+            Some(v_inv) => (g * v_inv) % n,
+            None => return false,
+        };
+
+        proofs::ni_poke2_verify(other, &k, pi_b, n)
+    }
+}
+
+impl<S: AccumulatedSet> StaticAccumulator for GenericAccumulator<S> {
     /// Returns the current public state.
     fn state(&self) -> &BigUint {
         &self.root
     }
 
+    fn modulus(&self) -> &BigUint {
+        &self.n
+    }
+
+    fn generator(&self) -> &BigUint {
+        &self.g
+    }
+
     /// Generates a group of unknown order and initializes the group with a generator of that group.
     /// Setup(λ, z) → pp, A0 Generate the public parameters
     fn setup<T, R>(rng: &mut R, int_size_bits: usize) -> Self
@@ -53,13 +258,16 @@ impl StaticAccumulator for Accumulator {
         // we choose not to store them.
 
         let (n, g) = T::generate_primes(rng, int_size_bits).unwrap();
+        let g_table = Arc::new(FixedBaseTable::new(&g, &n, DEFAULT_WINDOW, int_size_bits));
 
-        Accumulator {
+        GenericAccumulator {
             int_size_bits,
             root: g.clone(),
             g,
             n,
-            set: BigUint::one(),
+            set: S::empty(),
+            g_table,
+            g_table_max_bits: FIXED_BASE_MAX_BITS,
         }
     }
 
@@ -67,130 +275,120 @@ impl StaticAccumulator for Accumulator {
     #[inline]
     fn add(&mut self, x: &BigUint) {
         debug_assert!(
-            self.g.clone().modpow(&self.set, &self.n) == self.root,
+            self.g_pow(&self.set.product()) == self.root,
             "invalid state - pre add"
         );
 
         // assumes x is already a prime
-        self.set *= x;
-        self.root = self.root.modpow(x, &self.n);
+        self.set.insert(x);
+        self.root = backend::modpow(&self.root, x, &self.n);
+        self.extend_g_table();
     }
 
     //A membership witness is simply the accumulator without the aggregated item.
     #[inline]
     fn mem_wit_create(&self, x: &BigUint) -> BigUint {
         debug_assert!(
-            self.g.clone().modpow(&self.set, &self.n) == self.root,
+            self.g_pow(&self.set.product()) == self.root,
             "invalid state"
         );
 
-        let (set, r) = self.set.clone().div_rem(x);
-        debug_assert!(r.is_zero(), "x was not a valid member of set");
-
-        self.g.clone().modpow(&set, &self.n)
+        self.g_pow(&self.set.quotient(x))
     }
 
     #[inline]
     fn ver_mem(&self, w: &BigUint, x: &BigUint) -> bool {
-        w.modpow(x, &self.n) == self.root
+        backend::modpow(w, x, &self.n) == self.root
+    }
+
+    #[inline]
+    fn contains(&self, x: &BigUint) -> bool {
+        self.set.contains(x)
     }
 }
 
-impl DynamicAccumulator for Accumulator {
+impl<S: AccumulatedSet> DynamicAccumulator for GenericAccumulator<S> {
+    /// The new state is `g` raised to the remaining product, using the precomputed powers of `g`.
+    /// This is linear in the size of the set, for every [AccumulatedSet], see
+    /// [BatchedAccumulator::batch_del] to delete with witnesses instead.
     #[inline]
     fn del(&mut self, x: &BigUint) -> Option<()> {
-        let old_s = self.set.clone();
-        self.set /= x;
-
-        if self.set == old_s {
-            return None;
-        }
-
-        self.root = self.g.clone().modpow(&self.set, &self.n); //Returns (self ^ exponent) % modulus.
+        self.set.remove(x)?;
+        self.root = self.g_pow(&self.set.product());
         Some(())
     }
 }
 
-impl UniversalAccumulator for Accumulator {
-    fn non_mem_wit_create(&self, x: &BigUint) -> (BigUint, BigInt) {
-        // set* <- \prod_{set\in S} set
-        let s_star = &self.set;
-
+impl<S: AccumulatedSet> UniversalAccumulator for GenericAccumulator<S> {
+    fn non_mem_wit_create(&self, x: &BigUint) -> Option<(BigUint, BigInt)> {
         // a, b <- Bezout(x, set*)
-        let (_, a, b) = ExtendedGcd::extended_gcd(x, s_star);
-        let d = modpow_uint_int(&self.g, &a, &self.n).expect("prime");
+        let (a, b) = self.bezout(x)?;
+        let d = self.g_pow_int(&a).expect("prime");
 
-        (d, b)
+        Some((d, b))
     }
 
     fn ver_non_mem(&self, w: &(BigUint, BigInt), x: &BigUint) -> bool {
         let (d, b) = w;
-
-        // A^b
-        let a_b = modpow_uint_int(&self.root, b, &self.n).expect("prime");
-        // d^x
-        let d_x = d.modpow(x, &self.n);
+        let x: BigInt = x.clone().into();
 
         // d^x A^b == g
-        (d_x * &a_b) % &self.n == self.g
+        multi_modpow_int(&[(d, &x), (&self.root, b)], &self.n).as_ref() == Some(&self.g)
     }
 }
 
-impl BatchedAccumulator for Accumulator {
+impl<S: AccumulatedSet> BatchedAccumulator for GenericAccumulator<S> {
     fn batch_add(&mut self, xs: &[BigUint]) -> BigUint {
         //begin our summation of the added elements
-        let mut x_star = BigUint::one();
-        for x in xs {
-            x_star *= x;
-            //add into element
-            self.set *= x;
-        }
+        let x_star = product(xs);
+        //add into element
+        self.set.insert_all(xs);
 
         //temp clone our old root
         let root_t = self.root.clone();
         //calculate our new root after all the added elements
-        self.root = self.root.modpow(&x_star, &self.n); //Returns (self ^ exponent) % modulus.
-                                                        //create our proof for the procedure
+        self.root = backend::modpow(&self.root, &x_star, &self.n); //Returns (self ^ exponent) % modulus.
+        self.extend_g_table();
+
+        //create our proof for the procedure
         proofs::ni_poe_prove(&x_star, &root_t, &self.root, &self.n)
     }
 
     fn ver_batch_add(&self, w: &BigUint, root: &BigUint, xs: &[BigUint]) -> bool {
-        let mut x_star = BigUint::one();
-        for x in xs {
-            x_star *= x
-        }
+        let x_star = product(xs);
 
         proofs::ni_poe_verify(&x_star, root, &self.root, w, &self.n)
     }
 
     fn batch_del(&mut self, pairs: &[(BigUint, BigUint)]) -> Option<BigUint> {
-        if pairs.is_empty() {
+        let ((x0, w0), rest) = pairs.split_first()?;
+
+        // check all elements and witnesses, before changing the set
+        if pairs
+            .iter()
+            .any(|(x, w)| !self.set.contains(x) || !self.ver_mem(w, x))
+        {
             return None;
         }
-        let mut pairs = pairs.iter();
-        let root_t = self.root.clone();
 
-        let (x0, w0) = pairs.next().unwrap();
+        // fails for elements which are not coprime, including duplicates
         let mut x_star = x0.clone();
         let mut new_root = w0.clone();
-
-        for (xi, wi) in pairs {
-            new_root = shamir_trick(&new_root, wi, &x_star, xi, &self.n).unwrap();
+        for (xi, wi) in rest {
+            new_root = shamir_trick(&new_root, wi, &x_star, xi, &self.n)?;
             x_star *= xi;
-            // for now this is not great, depends on this impl, not on the general design
-            self.set /= xi;
         }
 
-        self.root = new_root;
+        for (x, _) in pairs {
+            self.set.remove(x).expect("checked member");
+        }
+        let root_t = std::mem::replace(&mut self.root, new_root);
 
         Some(proofs::ni_poe_prove(&x_star, &self.root, &root_t, &self.n))
     }
 
     fn ver_batch_del(&self, w: &BigUint, root: &BigUint, xs: &[BigUint]) -> bool {
-        let mut x_star = BigUint::one();
-        for x in xs {
-            x_star *= x
-        }
+        let x_star = product(xs);
 
         proofs::ni_poe_verify(&x_star, &self.root, root, w, &self.n)
     }
@@ -200,7 +398,7 @@ impl BatchedAccumulator for Accumulator {
             return None;
         }
 
-        self.set /= x;
+        self.set.remove(x)?;
         // w is root without x, so need to recompute
         self.root = w.clone();
 
@@ -209,7 +407,18 @@ impl BatchedAccumulator for Accumulator {
 
     #[inline]
     fn create_all_mem_wit(&self, set: &[BigUint]) -> Vec<BigUint> {
-        root_factor(&self.g, set, &self.n)
+        root_factor_fixed_base(&self.g, &self.g_table, set, &self.n)
+    }
+
+    fn mem_wit_create_batch(&self, xs: &[BigUint]) -> Option<Vec<BigUint>> {
+        // all elements are distinct primes, so they are members iff their product is a factor
+        let (q, r) = self.set.product().div_rem(&product(xs));
+        if !r.is_zero() {
+            return None;
+        }
+
+        // the witnesses of all x_i are the (X / x_i)-th powers of the witness of X
+        Some(root_factor(&self.g_pow(&q), xs, &self.n))
     }
 
     fn agg_mem_wit(
@@ -224,7 +433,7 @@ impl BatchedAccumulator for Accumulator {
         let xy = x.clone() * y;
 
         debug_assert!(
-            w_xy.modpow(&xy, &self.n) == self.root,
+            backend::modpow(&w_xy, &xy, &self.n) == self.root,
             "invalid shamir trick"
         );
 
@@ -268,52 +477,17 @@ impl BatchedAccumulator for Accumulator {
             return false;
         }
 
-        // A_1^y
-        let rhs_a = self.root.modpow(y, &self.n);
-        // A_2^x
-        let rhs_b = other.modpow(x, &self.n);
-
         // A_1^y * A_2^x
-        let rhs = (rhs_a * rhs_b) % &self.n;
+        let rhs = multi_modpow(&[(&self.root, y), (other, x)], &self.n);
         // pi^{x * y}
-        let lhs = pi.modpow(&(x.clone() * y), &self.n);
+        let lhs = backend::modpow(pi, &(x.clone() * y), &self.n);
 
         lhs == rhs
     }
 
-    fn non_mem_wit_create_star(
-        &self,
-        x: &BigUint,
-    ) -> (BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint) {
-        let g = &self.g;
-        let n = &self.n;
-
-        // a, b <- Bezout(x, s_star)
-
-        let (_, a, b) = ExtendedGcd::extended_gcd(x, &self.set);
-
-        // d <- g^a
-        let d = modpow_uint_int(g, &a, n).expect("invalid state");
-        // v <- A^b
-        let v = modpow_uint_int(&self.root, &b, n).expect("invalid state");
-
-        // pi_d <- NI-PoKE2(b, A, v)
-        let pi_d = proofs::ni_poke2_prove(b, &self.root, &v, n);
-
-        // k <- g * v^-1
-        let k = (g * v
-            .clone()
-            .mod_inverse(n)
-            .expect("invalid state")
-            .into_biguint()
-            .unwrap())
-            % n;
-
-        // pi_g <- NI-PoE(x, d, g * v^-1)
-        let pi_g = proofs::ni_poe_prove(x, &d, &k, n);
-
-        // return {d, v, pi_d, pi_g}
-        (d, v, pi_d, pi_g)
+    fn non_mem_wit_create_star(&self, x: &BigUint) -> Option<NonMemStarProof> {
+        let w = self.non_mem_wit_create(x)?;
+        Some(non_mem_wit_star(x, &w, &self.root, &self.n))
     }
 
     fn ver_non_mem_star(
@@ -321,31 +495,59 @@ impl BatchedAccumulator for Accumulator {
         x: &BigUint,
         pi: &(BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint),
     ) -> bool {
-        let g = &self.g;
-        let n = &self.n;
+        ver_non_mem_star(x, pi, &self.root, &self.g, &self.n)
+    }
+}
 
-        let (d, v, pi_d, pi_g) = pi;
+/// Creates the proof of [BatchedAccumulator::non_mem_wit_create_star], for the non-membership
+/// witness `(d, b)` of `x` in the accumulator with state `root`.
+pub fn non_mem_wit_star(
+    x: &BigUint,
+    w: &(BigUint, BigInt),
+    root: &BigUint,
+    n: &BigUint,
+) -> (BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint) {
+    let (d, b) = w;
 
-        // verify NI-PoKE2
-        if !proofs::ni_poke2_verify(&self.root, v, pi_d, n) {
-            return false;
-        }
+    // v <- A^b
+    let v = secret_modpow_int(root, b, n).expect("invalid state");
 
-        // verify NI-PoE
-        let k = (g * v
-            .clone()
-            .mod_inverse(n)
-            .expect("invalid state")
-            .into_biguint()
-            .unwrap())
-            % n;
+    // pi_d <- NI-PoKE2(b, A, v)
+    let pi_d = proofs::ni_poke2_prove(b.clone(), root, &v, n);
 
-        if !proofs::ni_poe_verify(x, d, &k, pi_g, n) {
-            return false;
-        }
+    // k <- g * v^-1 = d^x
+    let k = backend::modpow(d, x, n);
+
+    // pi_g <- NI-PoE(x, d, g * v^-1)
+    let pi_g = proofs::ni_poe_prove(x, d, &k, n);
+
+    // return {d, v, pi_d, pi_g}
+    (d.clone(), v, pi_d, pi_g)
+}
 
-        true
+/// Verifies the proof of [BatchedAccumulator::non_mem_wit_create_star], that `x` is not a member
+/// of the accumulator with state `root` and generator `g`.
+pub fn ver_non_mem_star(
+    x: &BigUint,
+    pi: &(BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint),
+    root: &BigUint,
+    g: &BigUint,
+    n: &BigUint,
+) -> bool {
+    let (d, v, pi_d, pi_g) = pi;
+
+    // verify NI-PoKE2
+    if !proofs::ni_poke2_verify(root, v, pi_d, n) {
+        return false;
     }
+
+    // verify NI-PoE
+    let k = match mod_inverse(v, n) {
+        Some(v_inv) => (g * v_inv) % n,
+        None => return false,
+    };
+
+    proofs::ni_poe_verify(x, d, &k, pi_g, n)
 }
 
 #[cfg(test)]
@@ -353,6 +555,7 @@ mod tests {
     use super::*;
 
     use crate::group::RSAGroup;
+    use crate::math::modpow_uint_int;
     use num_bigint::RandPrime;
     use num_bigint::Sign;
     use num_traits::FromPrimitive;
@@ -435,12 +638,90 @@ mod tests {
             for _ in 0..5 {
                 let y = rng.gen_prime(int_size_bits);
 
-                let w = acc.non_mem_wit_create(&y);
+                let w = acc.non_mem_wit_create(&y).unwrap();
                 assert!(acc.ver_non_mem(&w, &y));
             }
         }
     }
 
+    #[test]
+    fn test_chunked() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let int_size_bits = 256; // insecure, but faster tests
+
+        let mut acc = Accumulator::setup::<RSAGroup, _>(rng, int_size_bits);
+        let mut chunked = ChunkedAccumulator::setup::<RSAGroup, _>(rng, int_size_bits);
+        // use the same group for both
+        chunked.g = acc.g.clone();
+        chunked.n = acc.n.clone();
+        chunked.root = acc.root.clone();
+        chunked.g_table = acc.g_table.clone();
+        chunked.set = ChunkedSet::new(3);
+
+        let xs = (0..20)
+            .map(|_| rng.gen_prime(int_size_bits))
+            .collect::<Vec<_>>();
+
+        acc.batch_add(&xs[..10]);
+        chunked.batch_add(&xs[..10]);
+        for x in &xs[10..] {
+            acc.add(x);
+            chunked.add(x);
+        }
+
+        for x in xs.iter().step_by(3) {
+            acc.del(x).unwrap();
+            chunked.del(x).unwrap();
+        }
+        assert_eq!(acc.state(), chunked.state());
+
+        for (i, x) in xs.iter().enumerate() {
+            if i % 3 == 0 {
+                let w = chunked.non_mem_wit_create(x).unwrap();
+                assert_eq!(Some(w.clone()), acc.non_mem_wit_create(x));
+                assert!(chunked.ver_non_mem(&w, x));
+            } else {
+                let w = chunked.mem_wit_create(x);
+                assert_eq!(w, acc.mem_wit_create(x));
+                assert!(chunked.ver_mem(&w, x));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_base_table() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let int_size_bits = 256; // insecure, but faster tests
+
+        let mut acc = Accumulator::setup::<RSAGroup, _>(rng, int_size_bits);
+        let xs = (0..8)
+            .map(|_| rng.gen_prime(int_size_bits))
+            .collect::<Vec<_>>();
+        acc.batch_add(&xs[..4]);
+
+        // clones share the table, until one of them extends it
+        let mut other = acc.clone();
+        assert!(Arc::ptr_eq(&acc.g_table, &other.g_table));
+        other.batch_add(&xs[4..]);
+        assert!(!Arc::ptr_eq(&acc.g_table, &other.g_table));
+        assert!(other.g_table.bits() >= other.set.bits());
+
+        // a lower limit drops the larger powers, and falls back to modpow
+        other.set_fixed_base_max_bits(512);
+        assert!(other.g_table.bits() >= 512 && other.g_table.bits() < other.set.bits());
+        assert_eq!(
+            other.g_pow(&other.set.product()),
+            backend::modpow(&other.g, &other.set.product(), &other.n)
+        );
+        for x in &xs {
+            let w = other.mem_wit_create(x);
+            assert!(other.ver_mem(&w, x));
+        }
+
+        other.set_fixed_base_max_bits(FIXED_BASE_MAX_BITS);
+        assert!(other.g_table.bits() >= other.set.bits());
+    }
+
     #[test]
     fn test_math_non_mempership() {
         let rng = &mut ChaChaRng::from_seed([0u8; 32]);
@@ -462,7 +743,7 @@ mod tests {
         // A = g ^ set*
         let root = g.modpow(&s_star, &n);
 
-        let (_, a, b) = ExtendedGcd::extended_gcd(&x, &s_star);
+        let (_, a, b) = extended_gcd(&x, &s_star);
         println!("{} {} {} {}", &g, &a, &b, &n);
 
         let u = BigInt::from_biguint(Sign::Plus, x.clone());
@@ -557,6 +838,62 @@ mod tests {
             acc.ver_batch_del(&w, &root, &set[..3]),
             "ver_batch_del failed"
         );
+
+        // the remaining members are still members
+        for x in &set[3..] {
+            let w = acc.mem_wit_create(x);
+            assert!(acc.ver_mem(&w, x), "invalid witness after batch_del");
+        }
+    }
+
+    #[test]
+    fn test_batch_del_invalid() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let int_size_bits = 256; // insecure, but faster tests
+
+        let mut acc = ChunkedAccumulator::setup::<RSAGroup, _>(rng, int_size_bits);
+        acc.set = ChunkedSet::new(2);
+        let xs = (0..6)
+            .map(|_| rng.gen_prime(int_size_bits))
+            .collect::<Vec<_>>();
+        acc.batch_add(&xs[..5]);
+        let ws = acc.create_all_mem_wit(&xs[..5]);
+        let pair = |i: usize| (xs[i].clone(), ws[i].clone());
+
+        // witnesses for a subset of the members
+        assert!(acc.contains(&xs[1]) && !acc.contains(&xs[5]));
+        assert_eq!(acc.mem_wit_create_batch(&xs[1..3]).unwrap(), &ws[1..3]);
+        assert_eq!(acc.mem_wit_create_batch(&xs[4..]), None);
+        assert_eq!(
+            acc.mem_wit_create_batch(&[xs[1].clone(), xs[1].clone()]),
+            None
+        );
+
+        let before = acc.clone();
+        let invalid = vec![
+            // not a member
+            vec![pair(0), (xs[5].clone(), ws[1].clone())],
+            // invalid witness
+            vec![pair(0), pair(1), (xs[2].clone(), ws[3].clone())],
+            // duplicate
+            vec![pair(0), pair(1), pair(0)],
+            vec![],
+        ];
+        for pairs in &invalid {
+            assert_eq!(acc.batch_del(pairs), None);
+            assert_eq!(acc.state(), before.state());
+            assert_eq!(acc.set, before.set);
+        }
+
+        let root = acc.state().clone();
+        let w = acc.batch_del(&[pair(3), pair(0)]).unwrap();
+        assert!(acc.ver_batch_del(&w, &root, &[xs[3].clone(), xs[0].clone()]));
+        assert_eq!(acc.state(), &acc.g_pow(&acc.set.product()));
+
+        // deleting without a witness recomputes the state from the remaining product
+        acc.del(&xs[2]).unwrap();
+        assert_eq!(acc.del(&xs[2]), None);
+        assert_eq!(acc.state(), &acc.g_pow(&(&xs[1] * &xs[4])));
     }
 
     #[test]
@@ -638,6 +975,15 @@ mod tests {
                 for (pi, x) in pis.iter().zip(&xs) {
                     assert!(acc.ver_mem_star(x, pi), "invalid mem_wit_create_star proof");
                 }
+                assert_eq!(acc.ver_mem_star_batch(&xs, &pis), Ok(()));
+
+                // missing proofs, or missing elements
+                assert_eq!(acc.ver_mem_star_batch(&xs, &pis[..4]), Err(4));
+                assert_eq!(acc.ver_mem_star_batch(&xs[..4], &pis), Err(4));
+
+                let mut pis = pis;
+                pis.swap(1, 3);
+                assert_eq!(acc.ver_mem_star_batch(&xs, &pis), Err(1));
             }
 
             // MemWitX
@@ -667,6 +1013,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_subset() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        for _ in 0..10 {
+            let int_size_bits = 256; // insecure, but faster tests
+            let mut acc = Accumulator::setup::<RSAGroup, _>(rng, int_size_bits);
+            let mut sub = acc.clone();
+
+            let xs = (0..5)
+                .map(|_| rng.gen_prime(int_size_bits))
+                .collect::<Vec<_>>();
+
+            for x in &xs {
+                acc.add(x);
+            }
+            for x in &xs[..3] {
+                sub.add(x);
+            }
+
+            let pi = acc.prove_subset(&sub).expect("sub is a subset");
+            assert!(acc.ver_subset(sub.state(), &pi), "invalid subset proof");
+            assert!(
+                !sub.ver_subset(acc.state(), &pi),
+                "subset proof verified in the wrong direction"
+            );
+
+            // not a subset anymore
+            sub.add(&rng.gen_prime(int_size_bits));
+            assert!(acc.prove_subset(&sub).is_none());
+
+            // different groups
+            let other = Accumulator::setup::<RSAGroup, _>(rng, int_size_bits);
+            assert!(acc.prove_subset(&other).is_none());
+        }
+    }
+
+    #[test]
+    fn test_disjoint() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        for _ in 0..10 {
+            let int_size_bits = 256; // insecure, but faster tests
+            let mut acc = Accumulator::setup::<RSAGroup, _>(rng, int_size_bits);
+            let mut other = acc.clone();
+
+            let xs = (0..8)
+                .map(|_| rng.gen_prime(int_size_bits))
+                .collect::<Vec<_>>();
+
+            for x in &xs[..5] {
+                acc.add(x);
+            }
+            for x in &xs[5..] {
+                other.add(x);
+            }
+
+            let pi = acc.prove_disjoint(&other).expect("sets are disjoint");
+            assert!(
+                acc.ver_disjoint(other.state(), &pi),
+                "invalid disjointness proof"
+            );
+
+            // share an element
+            other.add(&xs[0]);
+            assert!(acc.prove_disjoint(&other).is_none());
+            assert!(
+                !acc.ver_disjoint(other.state(), &pi),
+                "stale disjointness proof verified"
+            );
+        }
+    }
+
     #[test]
     fn test_aggregation_non_mem_star() {
         let rng = &mut ChaChaRng::from_seed([0u8; 32]);
@@ -685,7 +1104,7 @@ mod tests {
             }
 
             let x = rng.gen_prime(int_size_bits);
-            let pi = acc.non_mem_wit_create_star(&x);
+            let pi = acc.non_mem_wit_create_star(&x).unwrap();
 
             assert!(acc.ver_non_mem_star(&x, &pi), "invalid ver_non_mem_star");
         }
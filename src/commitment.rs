@@ -0,0 +1,238 @@
+//! Damgård-Fujisaki integer commitments in groups of unknown order.
+//!
+//! An integer `x` is committed to as `C = g^x h^r mod n`, where `h <- H_G(g, n)` and `r` is
+//! random with `|n| + STAT_SECURITY_BITS` bits. Commitments are statistically hiding, and binding
+//! as long as the order of the group and the discrete logarithm of `h` to the base `g` are unknown.
+//!
+//! Proofs about committed values are sigma protocols, made non-interactive using Fiat-Shamir. As
+//! the group order is unknown, secrets are blinded over the integers, and responses are signed
+//! integers `k - c s`.
+
+use blake2::Blake2b512;
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_traits::One;
+use rand::{CryptoRng, Rng};
+
+use crate::hash::hash_group;
+use crate::math::{multi_modpow_int, secret_modpow_int};
+use crate::proofs::Transcript;
+use crate::traits::PrimeGroup;
+
+/// Size of the Fiat-Shamir challenges, in bits.
+pub const CHALLENGE_BITS: usize = 128;
+
+/// Statistical zero-knowledge parameter, the blinding integers exceed the blinded values by this
+/// many bits.
+pub const STAT_SECURITY_BITS: usize = 80;
+
+/// The public parameters `(g, h, n)` of the commitment scheme.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitmentKey {
+    g: BigUint,
+    h: BigUint,
+    n: BigUint,
+}
+
+/// Proof of knowledge of an opening `(x, r)` of a commitment.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpeningProof {
+    pub challenge: BigUint,
+    pub z_x: BigInt,
+    pub z_r: BigInt,
+}
+
+impl CommitmentKey {
+    /// Creates the key for the group `n` and base `g`, deriving `h <- H_G(g, n)`.
+    pub fn new(g: &BigUint, n: &BigUint) -> Self {
+        let mut input = g.to_bytes_be();
+        input.extend(&n.to_bytes_be());
+        let h = hash_group::<_, Blake2b512>(&input, n);
+
+        CommitmentKey {
+            g: g.clone(),
+            h,
+            n: n.clone(),
+        }
+    }
+
+    /// Generates a new group of unknown order, and creates the key for it.
+    pub fn setup<T, R>(rng: &mut R, int_size_bits: usize) -> Self
+    where
+        T: PrimeGroup,
+        R: CryptoRng + Rng,
+    {
+        let (n, g) = T::generate_primes(rng, int_size_bits).unwrap();
+
+        Self::new(&g, &n)
+    }
+
+    pub fn g(&self) -> &BigUint {
+        &self.g
+    }
+
+    pub fn h(&self) -> &BigUint {
+        &self.h
+    }
+
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    /// Samples randomness for a commitment, or for hiding a group element.
+    pub fn randomness<R: CryptoRng + Rng>(&self, rng: &mut R) -> BigUint {
+        rng.gen_biguint(self.n.bits() + STAT_SECURITY_BITS)
+    }
+
+    /// Commits to `x`, returning the commitment and the randomness `r` needed to open it.
+    pub fn commit<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        x: impl Into<BigInt>,
+    ) -> (BigUint, BigUint) {
+        let r = self.randomness(rng);
+        let c = self.commit_with(&x.into(), &r);
+
+        (c, r)
+    }
+
+    /// Calculates the commitment `g^x h^r`.
+    pub fn commit_with(&self, x: &BigInt, r: &BigUint) -> BigUint {
+        let r = BigInt::from(r.clone());
+        secret_multi_modpow(&[(&self.g, x), (&self.h, &r)], &self.n).expect("invalid state")
+    }
+
+    /// Checks that `(x, r)` opens the commitment `c`.
+    pub fn open(&self, c: &BigUint, x: &BigInt, r: &BigUint) -> bool {
+        &self.commit_with(x, r) == c
+    }
+
+    /// Adds two commitments, the result opens to `(x_1 + x_2, r_1 + r_2)`.
+    pub fn add(&self, c_1: &BigUint, c_2: &BigUint) -> BigUint {
+        (c_1 * c_2) % &self.n
+    }
+
+    /// Proves knowledge of the opening `(x, r)` of the commitment `c`.
+    /// The proof reveals the bit length of `x`, at most.
+    pub fn prove_opening<R: CryptoRng + Rng>(
+        &self,
+        rng: &mut R,
+        c: &BigUint,
+        x: &BigInt,
+        r: &BigUint,
+    ) -> OpeningProof {
+        debug_assert!(self.open(c, x, r), "invalid input");
+
+        let r = BigInt::from(r.clone());
+        let k_x = blinding(rng, x.bits());
+        let k_r = blinding(rng, self.n.bits() + STAT_SECURITY_BITS);
+
+        // t <- g^{k_x} h^{k_r}
+        let t = secret_multi_modpow(&[(&self.g, &k_x), (&self.h, &k_r)], &self.n)
+            .expect("invalid state");
+        let c = challenge(self.transcript().append(c).append(&t));
+
+        OpeningProof {
+            z_x: response(&k_x, &c, x),
+            z_r: response(&k_r, &c, &r),
+            challenge: c,
+        }
+    }
+
+    /// Verifies knowledge of an opening of the commitment `c`.
+    pub fn verify_opening(&self, c: &BigUint, pi: &OpeningProof) -> bool {
+        let OpeningProof {
+            challenge: ch,
+            z_x,
+            z_r,
+        } = pi;
+
+        // t = c^ch g^{z_x} h^{z_r}
+        let ch_int = BigInt::from(ch.clone());
+        let t = match multi_modpow_int(&[(c, &ch_int), (&self.g, z_x), (&self.h, z_r)], &self.n) {
+            Some(t) => t,
+            None => return false,
+        };
+
+        &challenge(self.transcript().append(c).append(&t)) == ch
+    }
+
+    /// Returns a transcript, starting with the key.
+    pub(crate) fn transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new();
+        transcript.append(&self.g).append(&self.h).append(&self.n);
+        transcript
+    }
+}
+
+/// Samples the blinding integer for a secret of up to `bits` bits.
+pub(crate) fn blinding<R: CryptoRng + Rng>(rng: &mut R, bits: usize) -> BigInt {
+    BigInt::from(rng.gen_biguint(bits + CHALLENGE_BITS + STAT_SECURITY_BITS))
+}
+
+/// Returns the response `k - c s`.
+pub(crate) fn response(k: &BigInt, c: &BigUint, s: &BigInt) -> BigInt {
+    k - BigInt::from(c.clone()) * s
+}
+
+/// Returns the challenge for the given transcript.
+pub(crate) fn challenge(transcript: &Transcript) -> BigUint {
+    transcript.challenge_bytes(CHALLENGE_BITS / 8)
+}
+
+/// Calculates `\prod b_i^{e_i}`, for secret exponents `e_i`.
+/// Returns `None` if any negative power is not invertible.
+pub(crate) fn secret_multi_modpow(terms: &[(&BigUint, &BigInt)], n: &BigUint) -> Option<BigUint> {
+    let mut res = BigUint::one();
+    for (b, e) in terms {
+        res = (res * secret_modpow_int(b, e, n)?) % n;
+    }
+
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::group::RSAGroup;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_commitment() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let ck = CommitmentKey::setup::<RSAGroup, _>(rng, 256);
+
+        for x in &[0i64, 1, -1, 12345, -98765432123] {
+            let x = BigInt::from(*x);
+            let (c, r) = ck.commit(rng, x.clone());
+            assert!(ck.open(&c, &x, &r));
+            assert!(!ck.open(&c, &(&x + 1), &r));
+            assert!(!ck.open(&c, &x, &(&r + 1u32)));
+
+            let pi = ck.prove_opening(rng, &c, &x, &r);
+            assert!(ck.verify_opening(&c, &pi));
+
+            let mut bad = pi.clone();
+            bad.z_x += 1;
+            assert!(!ck.verify_opening(&c, &bad));
+            assert!(!ck.verify_opening(&ck.add(&c, ck.g()), &pi));
+        }
+    }
+
+    #[test]
+    fn test_commitment_add() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let ck = CommitmentKey::setup::<RSAGroup, _>(rng, 256);
+
+        let x_1 = BigInt::from(1000);
+        let x_2 = BigInt::from(-2345);
+        let (c_1, r_1) = ck.commit(rng, x_1.clone());
+        let (c_2, r_2) = ck.commit(rng, x_2.clone());
+
+        let c = ck.add(&c_1, &c_2);
+        assert!(ck.open(&c, &(x_1 + x_2), &(r_1 + r_2)));
+    }
+}
@@ -1,3 +1,66 @@
+use std::fmt;
 
+/// Errors of the vector commitments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VectorCommitmentError {
+    /// Appending `additional` entries to `len` entries would exceed the `capacity`.
+    CapacityExceeded {
+        len: usize,
+        additional: usize,
+        capacity: usize,
+    },
+    /// The `index` is not smaller than the length `len`.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// A value of `bits` bits does not fit into an entry of `width` bits.
+    ValueTooWide { bits: usize, width: usize },
+    /// The key is already present in the map.
+    KeyPresent,
+    /// The key is not present in the map.
+    KeyAbsent,
+    /// The given old value does not match the committed value.
+    ValueMismatch,
+    /// The `index` is updated more than once.
+    DuplicateIndex { index: usize },
+    /// The number of `values` differs from the number of `positions` they are opened at.
+    LengthMismatch { values: usize, positions: usize },
+}
 
-pub enum Errors {}
\ No newline at end of file
+impl fmt::Display for VectorCommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VectorCommitmentError::CapacityExceeded {
+                len,
+                additional,
+                capacity,
+            } => write!(
+                f,
+                "capacity exceeded: cannot append {} to {} of {} entries",
+                additional, len, capacity
+            ),
+            VectorCommitmentError::IndexOutOfBounds { index, len } => {
+                write!(f, "index {} out of bounds for length {}", index, len)
+            }
+            VectorCommitmentError::ValueTooWide { bits, width } => {
+                write!(
+                    f,
+                    "value of {} bits exceeds the width of {} bits",
+                    bits, width
+                )
+            }
+            VectorCommitmentError::KeyPresent => write!(f, "key is already present"),
+            VectorCommitmentError::KeyAbsent => write!(f, "key is not present"),
+            VectorCommitmentError::ValueMismatch => {
+                write!(f, "old value does not match the committed value")
+            }
+            VectorCommitmentError::DuplicateIndex { index } => {
+                write!(f, "index {} is updated more than once", index)
+            }
+            VectorCommitmentError::LengthMismatch { values, positions } => {
+                write!(f, "{} values given for {} positions", values, positions)
+            }
+        }
+    }
+}
+
+// implies `failure::Fail`
+impl std::error::Error for VectorCommitmentError {}
@@ -0,0 +1,260 @@
+//! Class groups of binary quadratic forms, in pure Rust.
+//!
+//! The group is given by a negative discriminant `d`, which can be derived from a public seed,
+//! such that no trusted setup is needed. Elements are the reduced forms `(a, b, c)`, with
+//! `b^2 - 4ac = d`, and composition follows Cohen, Algorithm 5.4.7.
+
+use blake2::{Blake2b512, Digest};
+use num_bigint::prime::probably_prime;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+use crate::math::backend;
+use crate::proofs::Transcript;
+use crate::traits::UnknownOrderGroup;
+
+/// A negative discriminant `d = 1 mod 8`, defining the class group of its forms.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Discriminant {
+    d: BigInt,
+}
+
+/// A reduced binary quadratic form `a x^2 + b x y + c y^2`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuadraticForm {
+    a: BigInt,
+    b: BigInt,
+    c: BigInt,
+}
+
+impl Discriminant {
+    /// Wraps `d`, returning `None` unless `d < 0` and `d = 1 mod 8`.
+    /// The class group only has unknown order if `-d` is a large prime, which is not checked.
+    pub fn new(d: BigInt) -> Option<Self> {
+        if !d.is_negative() || !d.mod_floor(&BigInt::from(8)).is_one() {
+            return None;
+        }
+
+        Some(Discriminant { d })
+    }
+
+    /// Derives the discriminant `d = -p` from `seed`, for the smallest prime `p = 7 mod 8` above
+    /// a `bits` sized hash of the seed.
+    pub fn from_seed(seed: &[u8], bits: usize) -> Self {
+        assert!(bits >= 8, "discriminant too small");
+
+        let len = Integer::div_ceil(&bits, &8);
+        let mut bytes = Vec::with_capacity(len + 64);
+        let mut counter = 0u64;
+        while bytes.len() < len {
+            let mut hasher = Blake2b512::new();
+            hasher.update(seed);
+            hasher.update(counter.to_be_bytes());
+            bytes.extend_from_slice(&hasher.finalize());
+            counter += 1;
+        }
+
+        let mut p = BigUint::from_bytes_be(&bytes[..len]) >> (len * 8 - bits);
+        p |= BigUint::one() << (bits - 1);
+        let rem = &p % 8u32;
+        p = p - rem + 7u32;
+        while !probably_prime(&p, 20) {
+            p += 8u32;
+        }
+
+        Discriminant {
+            d: -BigInt::from(p),
+        }
+    }
+
+    /// Returns `d`.
+    pub fn value(&self) -> &BigInt {
+        &self.d
+    }
+
+    /// Returns the reduced form `(a, b, c)`, if `b^2 - d` is a positive multiple of `4a`.
+    pub fn form(&self, a: BigInt, b: BigInt) -> Option<QuadraticForm> {
+        if !a.is_positive() {
+            return None;
+        }
+
+        let (c, r) = (&b * &b - &self.d).div_rem(&(&a * 4));
+        if !r.is_zero() {
+            return None;
+        }
+
+        Some(QuadraticForm { a, b, c }.reduce())
+    }
+
+    /// Returns the form `(2, 1, (1 - d) / 8)`.
+    pub fn generator(&self) -> QuadraticForm {
+        self.form(BigInt::from(2), BigInt::one())
+            .expect("d = 1 mod 8")
+    }
+}
+
+impl QuadraticForm {
+    pub fn a(&self) -> &BigInt {
+        &self.a
+    }
+
+    pub fn b(&self) -> &BigInt {
+        &self.b
+    }
+
+    pub fn c(&self) -> &BigInt {
+        &self.c
+    }
+
+    /// Moves `b` into `(-a, a]`.
+    fn normalize(self) -> Self {
+        let QuadraticForm { a, b, c } = self;
+
+        // r <- floor((a - b) / 2a)
+        let r = (&a - &b).div_floor(&(&a * 2));
+        let c = &a * &r * &r + &b * &r + c;
+        let b = b + &r * &a * 2;
+
+        QuadraticForm { a, b, c }
+    }
+
+    /// Returns the unique reduced form, `|b| <= a <= c`, with `b >= 0` if `a = |b|` or `a = c`.
+    fn reduce(self) -> Self {
+        let mut f = self.normalize();
+        while f.a > f.c || (f.a == f.c && f.b.is_negative()) {
+            let QuadraticForm { a, b, c } = f;
+
+            // s <- floor((c + b) / 2c)
+            let s = (&c + &b).div_floor(&(&c * 2));
+            let a_new = &c * &s * &s - &b * &s + a;
+            let b_new = &s * &c * 2 - b;
+
+            f = QuadraticForm {
+                a: c,
+                b: b_new,
+                c: a_new,
+            };
+        }
+
+        f.normalize()
+    }
+}
+
+/// Returns `(k, m / gcd(a, m))`, such that the solutions of `a x = b mod m` are `k + m / gcd(a, m) Z`.
+/// `b` must be a multiple of `gcd(a, m)`.
+fn solve_mod(a: &BigInt, b: &BigInt, m: &BigInt) -> (BigInt, BigInt) {
+    let a = a.mod_floor(m).to_biguint().expect("non negative");
+    let (g, d, _) = backend::extended_gcd(&a, &m.to_biguint().expect("positive"));
+
+    let (q, r) = b.div_mod_floor(&g);
+    debug_assert!(r.is_zero(), "no solution");
+
+    let step = m / &g;
+    ((q * d).mod_floor(&step), step)
+}
+
+impl UnknownOrderGroup for Discriminant {
+    type Elem = QuadraticForm;
+
+    fn identity(&self) -> QuadraticForm {
+        self.form(BigInt::one(), BigInt::one())
+            .expect("d = 1 mod 4")
+    }
+
+    fn op(&self, f1: &QuadraticForm, f2: &QuadraticForm) -> QuadraticForm {
+        let g = (&f2.b + &f1.b) / 2;
+        let h = (&f2.b - &f1.b) / 2;
+        let w = f1.a.gcd(&f2.a).gcd(&g);
+
+        let s = &f1.a / &w;
+        let t = &f2.a / &w;
+        let u = &g / &w;
+        let st = &s * &t;
+
+        // (t u) k = h u + s c1 mod s t
+        let (k_temp, step) = solve_mod(&(&t * &u), &(&h * &u + &s * &f1.c), &st);
+        let (n, _) = solve_mod(&(&t * &step), &(&h - &t * &k_temp), &s);
+        let k = k_temp + step * n;
+
+        let l = (&t * &k - &h) / &s;
+        let m = (&t * &u * &k - &h * &u - &f1.c * &s) / &st;
+
+        QuadraticForm {
+            b: &w * &u - (&k * &t + &l * &s),
+            c: &k * &l - &w * &m,
+            a: st,
+        }
+        .reduce()
+    }
+
+    fn transcribe(&self, f: &QuadraticForm, transcript: &mut Transcript) {
+        // c is determined by a, b and d
+        transcript
+            .append(&f.a.to_biguint().expect("positive"))
+            .append(&f.b.abs().to_biguint().expect("positive"))
+            .append(&BigUint::from((f.b.sign() == Sign::Minus) as u8));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discriminant() {
+        let d = Discriminant::from_seed(b"seed", 256);
+        assert_eq!(d.value().bits(), 256);
+        assert!(probably_prime(&(-d.value()).to_biguint().unwrap(), 20));
+        assert_eq!(Discriminant::new(d.value().clone()), Some(d.clone()));
+        assert_eq!(Discriminant::from_seed(b"seed", 256), d);
+        assert_ne!(Discriminant::from_seed(b"other seed", 256), d);
+
+        assert_eq!(Discriminant::new(BigInt::from(-5)), None);
+        assert_eq!(Discriminant::new(BigInt::from(17)), None);
+        assert!(Discriminant::new(BigInt::from(-23)).is_some());
+    }
+
+    #[test]
+    fn test_class_group() {
+        let d = Discriminant::from_seed(b"test", 512);
+        let g = d.generator();
+        let one = d.identity();
+
+        assert_eq!(d.op(&g, &one), g);
+        assert_eq!(d.op(&one, &g), g);
+
+        let g2 = d.square(&g);
+        let g3 = d.op(&g2, &g);
+        assert_eq!(d.op(&g, &g2), g3);
+        assert_eq!(d.pow(&g, &BigUint::from(3u32)), g3);
+
+        // (g^a)^b = g^(a b), g^a g^b = g^(a + b)
+        let a = BigUint::from(123_456u32);
+        let b = BigUint::from(987_654u32);
+        let ga = d.pow(&g, &a);
+        let gb = d.pow(&g, &b);
+        assert_eq!(d.pow(&ga, &b), d.pow(&gb, &a));
+        assert_eq!(d.op(&ga, &gb), d.pow(&g, &(&a + &b)));
+        assert_eq!(
+            d.repeated_square(&g, 10),
+            d.pow(&g, &BigUint::from(1024u32))
+        );
+
+        for f in &[&g, &g2, &g3, &ga, &gb] {
+            assert!(f.b.abs() <= f.a && f.a <= f.c);
+            assert_eq!(&f.b * &f.b - &f.a * &f.c * 4, d.d);
+        }
+
+        // the class group of d = -23 has order 3
+        let d = Discriminant::new(BigInt::from(-23)).unwrap();
+        let g = d.generator();
+        assert_ne!(g, d.identity());
+        assert_eq!(d.pow(&g, &BigUint::from(3u32)), d.identity());
+
+        assert_eq!(d.form(BigInt::from(5), BigInt::one()), None);
+        assert_eq!(d.form(BigInt::from(-2), BigInt::one()), None);
+    }
+}
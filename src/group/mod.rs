@@ -1,5 +1,11 @@
 //! Prime Group Backend for our Accumulstors
 
+//
+//Class groups of binary quadratic forms, for the verifiable delay functions
+//
+mod form;
+pub use self::form::{Discriminant, QuadraticForm};
+
 //
 //Classgroup Backend
 //
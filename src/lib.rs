@@ -12,13 +12,22 @@ extern crate serde;
 extern crate classygroup;
 
 pub mod accumulator;
+pub mod commitment;
+pub mod error;
 pub mod group;
 pub mod hash;
 pub mod math;
+mod parallel;
 pub mod proofs;
+pub mod set;
 pub mod traits;
 pub mod vc;
+pub mod vdf;
+pub mod witness_store;
+pub mod zk;
 
 pub use self::accumulator::*;
+pub use self::error::*;
 pub use self::traits::*;
 pub use self::vc::*;
+pub use self::witness_store::*;
@@ -0,0 +1,141 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer as _;
+use num_traits::Zero;
+use rug::integer::Order;
+use rug::Integer;
+
+use crate::math::montgomery::{limb_count, to_limbs};
+
+/// Modular arithmetic context using GMP, with the interface of the pure Rust Montgomery context,
+/// which it replaces with the `gmp` feature enabled.
+///
+/// GMP has no interface for Montgomery multiplication, so residues are GMP integers in `[0, n)`,
+/// reduced by division after each multiplication. Values are only converted by
+/// [GmpContext::to_repr] and [GmpContext::from_repr].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GmpContext {
+    n: BigUint,
+    n_gmp: Integer,
+}
+
+/// An element in the representation of a [GmpContext].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GmpResidue(Integer);
+
+impl GmpContext {
+    pub fn new(n: &BigUint) -> Self {
+        assert!(!n.is_zero(), "divide by zero!");
+
+        GmpContext {
+            n: n.clone(),
+            n_gmp: to_gmp(n),
+        }
+    }
+
+    /// Returns the modulus.
+    pub fn modulus(&self) -> &BigUint {
+        &self.n
+    }
+
+    /// Returns the representation of `1`.
+    pub fn one(&self) -> GmpResidue {
+        GmpResidue(Integer::from(1) % &self.n_gmp)
+    }
+
+    /// Converts `a` into its representation.
+    pub fn to_repr(&self, a: &BigUint) -> GmpResidue {
+        GmpResidue(to_gmp(a) % &self.n_gmp)
+    }
+
+    /// Converts a representation back into `a`.
+    pub fn from_repr(&self, a: &GmpResidue) -> BigUint {
+        from_gmp(&a.0)
+    }
+
+    /// Multiplies two representations.
+    pub fn mul(&self, a: &GmpResidue, b: &GmpResidue) -> GmpResidue {
+        GmpResidue(Integer::from(&a.0 * &b.0) % &self.n_gmp)
+    }
+
+    /// Squares a representation.
+    pub fn square(&self, a: &GmpResidue) -> GmpResidue {
+        GmpResidue(Integer::from(a.0.square_ref()) % &self.n_gmp)
+    }
+
+    /// Squares a representation `t` times, reusing the memory of the result.
+    pub fn repeated_square(&self, a: &GmpResidue, t: u64) -> GmpResidue {
+        let mut res = a.0.clone();
+        for _ in 0..t {
+            res.square_mut();
+            res %= &self.n_gmp;
+        }
+
+        GmpResidue(res)
+    }
+
+    /// Calculates `a^e % n`, for a representation `a`, returning a representation.
+    pub fn pow(&self, a: &GmpResidue, e: &BigUint) -> GmpResidue {
+        let res =
+            a.0.pow_mod_ref(&to_gmp(e), &self.n_gmp)
+                .map(Integer::from)
+                .expect("exponent is not negative");
+
+        GmpResidue(res)
+    }
+}
+
+pub fn modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    let n = to_gmp(n);
+    let res = to_gmp(b)
+        .pow_mod_ref(&to_gmp(e), &n)
+        .map(Integer::from)
+        .expect("exponent is not negative");
+
+    from_gmp(&res)
+}
+
+pub fn secure_modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    // GMP requires a positive exponent and an odd modulus
+    if e.is_zero() || n.is_even() {
+        return crate::math::ct_modpow(b, e, n);
+    }
+
+    let n = to_gmp(n);
+    let res = to_gmp(b).secure_pow_mod_ref(&to_gmp(e), &n).into();
+
+    from_gmp(&res)
+}
+
+pub fn mod_inverse(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    let n = to_gmp(n);
+    to_gmp(a)
+        .invert_ref(&n)
+        .map(|a_inv| from_gmp(&Integer::from(a_inv)))
+}
+
+pub fn extended_gcd(x: &BigUint, y: &BigUint) -> (BigInt, BigInt, BigInt) {
+    let y = to_gmp(y);
+    let (gcd, a, b) = <(Integer, Integer, Integer)>::from(to_gmp(x).gcd_cofactors_ref(&y));
+
+    (
+        from_gmp_signed(&gcd),
+        from_gmp_signed(&a),
+        from_gmp_signed(&b),
+    )
+}
+
+/// Converts `a`, copying its limbs.
+fn to_gmp(a: &BigUint) -> Integer {
+    Integer::from_digits(&to_limbs(a, limb_count(a)), Order::Lsf)
+}
+
+/// Converts a non negative `a`, copying its limbs.
+fn from_gmp(a: &Integer) -> BigUint {
+    BigUint::from_slice_native(&a.to_digits::<u64>(Order::Lsf))
+}
+
+fn from_gmp_signed(a: &Integer) -> BigInt {
+    let sign = if *a < 0 { Sign::Minus } else { Sign::Plus };
+    BigInt::from_biguint(sign, from_gmp(&Integer::from(a.abs_ref())))
+}
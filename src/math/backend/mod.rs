@@ -0,0 +1,166 @@
+//! Big integer backends for the expensive operations: exponentiations, inversions and gcds.
+//!
+//! All inputs and outputs are `num-bigint` integers, converted at the boundary, so the backends
+//! are interchangeable and produce identical results. With the `gmp` feature enabled, operations
+//! are done using GMP, otherwise using `num-bigint`.
+//!
+//! Sequences of modular multiplications, as in [crate::math::multi_modpow], the
+//! [crate::math::FixedBaseTable] and the VDFs, use a [crate::math::MontgomeryContext] instead,
+//! whose residues stay in the representation of the backend between operations. With the `gmp`
+//! feature enabled, it is replaced by [GmpContext].
+
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+
+#[cfg(feature = "gmp")]
+mod gmp;
+mod num;
+
+#[cfg(not(feature = "gmp"))]
+use self::num as imp;
+
+#[cfg(feature = "gmp")]
+use self::gmp as imp;
+
+#[cfg(feature = "gmp")]
+pub use self::gmp::{GmpContext, GmpResidue};
+
+/// Calculates `b^e % n`.
+pub fn modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    imp::modpow(b, e, n)
+}
+
+/// Calculates `b^e % n`, in time independent of the value of `e`.
+pub fn secure_modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    imp::secure_modpow(b, e, n)
+}
+
+/// Calculates `a^-1 % n`, if it exists.
+pub fn mod_inverse(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    imp::mod_inverse(a, n)
+}
+
+/// Returns `(gcd, a, b)`, such that `a x + b y = gcd`.
+///
+/// The coefficients are normalized to `0 <= a < y / gcd`, as backends are otherwise free to return
+/// any of the solutions. This differs from the coefficients returned by `num-bigint`'s
+/// `ExtendedGcd`, which were returned before the backends were introduced, e.g. for non membership
+/// witnesses, which are therefore different (but equally valid) ones than before.
+pub fn extended_gcd(x: &BigUint, y: &BigUint) -> (BigInt, BigInt, BigInt) {
+    let (gcd, a, b) = imp::extended_gcd(x, y);
+    if y.is_zero() || gcd.is_zero() {
+        return (gcd, a, b);
+    }
+
+    // (a + k y / gcd) x + (b - k x / gcd) y = gcd
+    let x = BigInt::from(x.clone());
+    let y = BigInt::from(y.clone());
+    let step = &y / &gcd;
+    let a_norm = a.mod_floor(&step);
+    let k = (&a_norm - &a) / &step;
+    let b_norm = b - k * (&x / &gcd);
+
+    debug_assert!(!a_norm.is_negative());
+    (gcd, a_norm, b_norm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::RandBigInt;
+    use num_traits::One;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_extended_gcd() {
+        let mut rng = thread_rng();
+
+        for bits in &[8, 128, 1024] {
+            for _ in 0..20 {
+                let x = rng.gen_biguint(*bits);
+                let y = rng.gen_biguint(*bits / 2) + 1u32;
+
+                let (gcd, a, b) = extended_gcd(&x, &y);
+                assert_eq!(gcd, BigInt::from(x.gcd(&y)));
+                assert_eq!(
+                    &a * BigInt::from(x.clone()) + &b * BigInt::from(y.clone()),
+                    gcd
+                );
+                assert!(!a.is_negative() && a < BigInt::from(&y / gcd.to_biguint().unwrap()));
+            }
+        }
+
+        let one = BigUint::one();
+        let (gcd, _, _) = extended_gcd(&one, &BigUint::zero());
+        assert_eq!(gcd, BigInt::one());
+    }
+
+    #[cfg(feature = "gmp")]
+    #[test]
+    fn test_backends_match() {
+        let mut rng = thread_rng();
+
+        for bits in &[64, 1024, 3072] {
+            for _ in 0..10 {
+                let n = rng.gen_biguint(*bits) | BigUint::one();
+                let b = rng.gen_biguint(*bits);
+                let e = rng.gen_biguint(*bits);
+
+                assert_eq!(gmp::modpow(&b, &e, &n), num::modpow(&b, &e, &n));
+                assert_eq!(gmp::secure_modpow(&b, &e, &n), num::modpow(&b, &e, &n));
+                assert_eq!(gmp::mod_inverse(&b, &n), num::mod_inverse(&b, &n));
+
+                let (gcd_gmp, a_gmp, b_gmp) = gmp::extended_gcd(&b, &n);
+                let (gcd_num, _, _) = num::extended_gcd(&b, &n);
+                assert_eq!(gcd_gmp, gcd_num);
+                assert_eq!(
+                    a_gmp * BigInt::from(b.clone()) + b_gmp * BigInt::from(n.clone()),
+                    gcd_gmp
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "gmp")]
+    #[test]
+    fn test_contexts_match() {
+        use crate::math::montgomery::MontgomeryContext;
+
+        let mut rng = thread_rng();
+
+        for bits in &[64, 1024, 3072] {
+            for _ in 0..10 {
+                // even moduli use the fallback of the Montgomery context
+                let n = rng.gen_biguint(*bits) + 1u32;
+                let a = rng.gen_biguint(*bits + 10);
+                let b = rng.gen_biguint(*bits + 10);
+                let e = rng.gen_biguint(300);
+
+                let ctx_num = MontgomeryContext::new(&n);
+                let ctx_gmp = GmpContext::new(&n);
+                let (a_num, b_num) = (ctx_num.to_repr(&a), ctx_num.to_repr(&b));
+                let (a_gmp, b_gmp) = (ctx_gmp.to_repr(&a), ctx_gmp.to_repr(&b));
+
+                assert_eq!(ctx_gmp.from_repr(&a_gmp), ctx_num.from_repr(&a_num));
+                assert_eq!(
+                    ctx_gmp.from_repr(&ctx_gmp.mul(&a_gmp, &b_gmp)),
+                    ctx_num.from_repr(&ctx_num.mul(&a_num, &b_num))
+                );
+                assert_eq!(
+                    ctx_gmp.from_repr(&ctx_gmp.repeated_square(&a_gmp, 20)),
+                    ctx_num.from_repr(&ctx_num.repeated_square(&a_num, 20))
+                );
+                assert_eq!(
+                    ctx_gmp.from_repr(&ctx_gmp.pow(&a_gmp, &e)),
+                    ctx_num.from_repr(&ctx_num.pow(&a_num, &e))
+                );
+                assert_eq!(
+                    ctx_gmp.from_repr(&ctx_gmp.one()),
+                    ctx_num.from_repr(&ctx_num.one())
+                );
+            }
+        }
+    }
+}
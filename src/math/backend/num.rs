@@ -0,0 +1,20 @@
+use num_bigint::traits::{ExtendedGcd, ModInverse};
+use num_bigint::{BigInt, BigUint, IntoBigUint};
+
+pub fn modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    b.modpow(e, n)
+}
+
+pub fn secure_modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    crate::math::ct_modpow(b, e, n)
+}
+
+pub fn mod_inverse(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    a.clone()
+        .mod_inverse(n)
+        .and_then(|a_inv| a_inv.into_biguint())
+}
+
+pub fn extended_gcd(x: &BigUint, y: &BigUint) -> (BigInt, BigInt, BigInt) {
+    ExtendedGcd::extended_gcd(x, y)
+}
@@ -0,0 +1,146 @@
+//! Constant time exponentiation and inversion, for secret exponents and values.
+//!
+//! These avoid branches and memory accesses depending on secret values, on a best effort basis,
+//! as the compiler gives no guarantees. Bases and moduli are considered public.
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::One;
+use rand::thread_rng;
+
+use super::backend;
+use super::montgomery::{limb_count, to_limbs, MontgomeryParams};
+
+/// Window size in bits, the table holds `2^CT_WINDOW` powers of the base.
+const CT_WINDOW: usize = 4;
+
+/// Calculates `b^e % n`, in time independent of the value of `e`.
+/// Only the number of limbs of `e` is leaked.
+///
+/// Uses a fixed window, and multiplies by a table entry for every window, selecting the entry
+/// by scanning the whole table. Even moduli, which do not occur for groups of unknown order, fall
+/// back to `modpow`.
+pub fn ct_modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    let params = match MontgomeryParams::new(n) {
+        Some(params) => params,
+        None => return b.modpow(e, n),
+    };
+
+    // table[i] <- b^i
+    let mut table = Vec::with_capacity(1 << CT_WINDOW);
+    table.push(params.one());
+    table.push(params.encode(b));
+    for i in 2..(1 << CT_WINDOW) {
+        let next = params.mul_ct(&table[i - 1], &table[1]);
+        table.push(next);
+    }
+
+    let e_limbs = to_limbs(e, limb_count(e));
+    let mut res = params.one();
+    for limb in e_limbs.iter().rev() {
+        for shift in (0..64 / CT_WINDOW).rev() {
+            for _ in 0..CT_WINDOW {
+                res = params.mul_ct(&res, &res);
+            }
+
+            let d = (limb >> (shift * CT_WINDOW)) & ((1 << CT_WINDOW) - 1);
+            res = params.mul_ct(&res, &select(&table, d));
+        }
+    }
+
+    params.decode_ct(&res)
+}
+
+/// Calculates `a^-1 % n`, for a secret `a`.
+///
+/// The inversion is done on `a r`, for a uniformly random unit `r`, and the result multiplied by
+/// `r` again, such that the timing of the extended gcd is independent of `a`.
+pub fn ct_mod_inverse(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    let mut rng = thread_rng();
+
+    // r <- random unit mod n
+    let r = loop {
+        let r = rng.gen_biguint_below(n);
+        if r.gcd(n).is_one() {
+            break r;
+        }
+    };
+
+    // a^-1 = (a r)^-1 r
+    let ar_inv = backend::mod_inverse(&((a * &r) % n), n)?;
+    Some((ar_inv * r) % n)
+}
+
+/// Returns `table[d]`, reading all entries.
+fn select(table: &[Vec<u64>], d: u64) -> Vec<u64> {
+    let mut res = vec![0; table[0].len()];
+    for (i, entry) in table.iter().enumerate() {
+        let mask = ct_eq(i as u64, d);
+        for (r, e) in res.iter_mut().zip(entry) {
+            *r |= e & mask;
+        }
+    }
+
+    res
+}
+
+/// Returns all ones if `a == b`, and zero otherwise.
+fn ct_eq(a: u64, b: u64) -> u64 {
+    let x = a ^ b;
+    // the top bit of `x | -x` is set iff x != 0
+    ((x | x.wrapping_neg()) >> 63).wrapping_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::RandPrime;
+    use num_traits::Zero;
+
+    #[test]
+    fn test_ct_eq() {
+        assert_eq!(ct_eq(0, 0), u64::MAX);
+        assert_eq!(ct_eq(3, 3), u64::MAX);
+        assert_eq!(ct_eq(3, 4), 0);
+        assert_eq!(ct_eq(0, u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_ct_modpow() {
+        let mut rng = thread_rng();
+
+        for bits in &[64, 128, 256, 1024] {
+            for _ in 0..10 {
+                let n = rng.gen_biguint(*bits) | BigUint::one();
+                let b = rng.gen_biguint(*bits);
+                let e = rng.gen_biguint(*bits + 100);
+
+                assert_eq!(ct_modpow(&b, &e, &n), b.modpow(&e, &n));
+                assert_eq!(ct_modpow(&b, &BigUint::zero(), &n), BigUint::one() % &n);
+            }
+        }
+
+        // even moduli
+        let n = BigUint::from(1000u32);
+        let b = BigUint::from(3u32);
+        let e = BigUint::from(12345u32);
+        assert_eq!(ct_modpow(&b, &e, &n), b.modpow(&e, &n));
+    }
+
+    #[test]
+    fn test_ct_mod_inverse() {
+        let mut rng = thread_rng();
+
+        for _ in 0..10 {
+            let n = rng.gen_prime(128) * rng.gen_prime(128);
+            let a = rng.gen_biguint_below(&n);
+
+            let a_inv = ct_mod_inverse(&a, &n).unwrap();
+            assert!(((a * a_inv) % &n).is_one());
+        }
+
+        let n = BigUint::from(15u32);
+        assert_eq!(ct_mod_inverse(&BigUint::from(5u32), &n), None);
+    }
+}
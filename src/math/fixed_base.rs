@@ -0,0 +1,159 @@
+use num_bigint::BigUint;
+use num_integer::Integer;
+
+use super::{MontgomeryContext, Residue};
+
+/// Default window size in bits, used for the precomputed tables.
+pub const DEFAULT_WINDOW: usize = 6;
+
+/// Precomputed powers `g^(2^(w i))` of a fixed base `g`, for exponentiations using Yao's method.
+///
+/// An exponentiation with an exponent of `k` bits costs about `k / w + 2^(w + 1)`
+/// multiplications and no squarings, compared to about `k` squarings using `modpow`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixedBaseTable {
+    /// Window size `w` in bits.
+    window: usize,
+    /// `powers[i] = g^(2^(w i))`, in Montgomery representation
+    powers: Vec<Residue>,
+}
+
+impl FixedBaseTable {
+    /// Creates a table for `g`, covering exponents of up to `bits` bits.
+    pub fn new(g: &BigUint, n: &BigUint, window: usize, bits: usize) -> Self {
+        assert!(window > 0 && window <= 16, "invalid window size");
+
+        let mut table = FixedBaseTable {
+            window,
+            powers: Vec::new(),
+        };
+        table.extend(g, n, bits);
+
+        table
+    }
+
+    /// Returns the number of exponent bits covered by this table.
+    pub fn bits(&self) -> usize {
+        self.window * self.powers.len()
+    }
+
+    /// Extends the table to cover exponents of up to `bits` bits.
+    /// `g` and `n` must be the same as used on creation.
+    pub fn extend(&mut self, g: &BigUint, n: &BigUint, bits: usize) {
+        if self.window == 0 {
+            self.window = DEFAULT_WINDOW;
+        }
+
+        let ctx = MontgomeryContext::new(n);
+        if self.powers.is_empty() {
+            self.powers.push(ctx.to_repr(g));
+        }
+
+        while self.bits() < bits {
+            let mut p = self.powers.last().unwrap().clone();
+            for _ in 0..self.window {
+                p = ctx.square(&p);
+            }
+            self.powers.push(p);
+        }
+    }
+
+    /// Shrinks the table to cover exponents of up to `bits` bits, dropping the larger powers.
+    pub fn truncate(&mut self, bits: usize) {
+        self.powers
+            .truncate(Integer::div_ceil(&bits, &self.window.max(1)));
+    }
+
+    /// Calculates `g^e % n`.
+    /// Returns `None` if `e` has more bits than covered by the table.
+    pub fn modpow(&self, e: &BigUint, n: &BigUint) -> Option<BigUint> {
+        if self.powers.is_empty() || e.bits() > self.bits() {
+            return None;
+        }
+
+        // group the powers by the digit of `e` they are raised to
+        let e_bytes = e.to_bytes_le();
+        let mut buckets = vec![Vec::new(); 1 << self.window];
+        for (i, g_i) in self.powers.iter().enumerate() {
+            let d_i = digit(&e_bytes, i * self.window, self.window);
+            if d_i > 0 {
+                buckets[d_i].push(g_i);
+            }
+        }
+
+        // \prod_i g_i^{d_i} = \prod_{j = 1}^{2^w - 1} (\prod_{d_i = j} g_i)^j
+        let ctx = MontgomeryContext::new(n);
+        let mut a = ctx.one();
+        let mut b = ctx.one();
+        for bucket in buckets.iter().skip(1).rev() {
+            for g_i in bucket {
+                b = ctx.mul(&b, g_i);
+            }
+            a = ctx.mul(&a, &b);
+        }
+
+        Some(ctx.from_repr(&a))
+    }
+}
+
+/// Reads the `len` bits starting at bit `offset` from the little endian bytes.
+fn digit(bytes: &[u8], offset: usize, len: usize) -> usize {
+    (0..len).fold(0, |d, j| {
+        let i = offset + j;
+        match bytes.get(i / 8) {
+            Some(byte) => d | ((((byte >> (i % 8)) & 1) as usize) << j),
+            None => d,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::RandBigInt;
+    use num_traits::{One, Zero};
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_fixed_base_modpow() {
+        let mut rng = thread_rng();
+
+        for window in 1..9 {
+            let n = rng.gen_biguint(256);
+            let g = rng.gen_biguint(256);
+
+            let table = FixedBaseTable::new(&g, &n, window, 1024);
+            assert!(table.bits() >= 1024);
+
+            for _ in 0..10 {
+                let bits = rng.gen_range(1..1024);
+                let e = rng.gen_biguint(bits);
+                assert_eq!(table.modpow(&e, &n), Some(g.modpow(&e, &n)));
+            }
+
+            assert_eq!(table.modpow(&BigUint::zero(), &n), Some(BigUint::one()));
+
+            let e = BigUint::one() << table.bits();
+            assert_eq!(table.modpow(&e, &n), None);
+        }
+    }
+
+    #[test]
+    fn test_fixed_base_extend() {
+        let mut rng = thread_rng();
+
+        let n = rng.gen_biguint(256);
+        let g = rng.gen_biguint(256);
+
+        let mut table = FixedBaseTable::default();
+        assert_eq!(table.modpow(&BigUint::one(), &n), None);
+
+        table.extend(&g, &n, 300);
+        assert_eq!(table, FixedBaseTable::new(&g, &n, DEFAULT_WINDOW, 300));
+
+        let e = rng.gen_biguint(300);
+        assert_eq!(table.modpow(&e, &n), Some(g.modpow(&e, &n)));
+    }
+}
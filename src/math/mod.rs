@@ -0,0 +1,399 @@
+#![cfg_attr(feature = "cargo-clippy", allow(clippy::many_single_char_names))]
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{One, Signed, Zero};
+
+pub(crate) mod backend;
+mod ct;
+mod fixed_base;
+#[cfg_attr(feature = "gmp", allow(dead_code))]
+mod montgomery;
+mod multiexp;
+mod product_tree;
+
+#[cfg(feature = "gmp")]
+pub use self::backend::{GmpContext as MontgomeryContext, GmpResidue as Residue};
+pub use self::ct::*;
+pub use self::fixed_base::*;
+#[cfg(not(feature = "gmp"))]
+pub use self::montgomery::{MontgomeryContext, Residue};
+pub use self::multiexp::*;
+pub use self::product_tree::*;
+
+use crate::parallel;
+
+/// Calculates a = a.pow(b).
+pub fn pow_assign(a: &mut BigUint, b: &BigUint) {
+    if b.is_zero() {
+        *a = BigUint::one();
+    } else if b.is_odd() {
+        let a_before = a.clone();
+        pow_assign(a, &(b.clone() - 1u32));
+        *a *= &a_before;
+    } else {
+        pow_assign(a, &(b.clone() / 2u32));
+        *a *= a.clone();
+    }
+}
+
+/// Calculates a ^ e % n.
+pub fn modpow_uint_int(a: &BigUint, e: &BigInt, n: &BigUint) -> Option<BigUint> {
+    match e.sign() {
+        Sign::Plus => {
+            // regular case
+            Some(backend::modpow(a, &e.to_biguint().unwrap(), n))
+        }
+        Sign::Minus => {
+            // exponent is negative, so we calculate the modular inverse of e.
+            backend::mod_inverse(a, n).map(|a_inv| {
+                let e_abs = e.abs().to_biguint().unwrap();
+                backend::modpow(&a_inv, &e_abs, n)
+            })
+        }
+        Sign::NoSign => {
+            // zero
+            Some(BigUint::one())
+        }
+    }
+}
+
+/// Calculates `b^e % n`, for a secret exponent `e`.
+/// With the `constant_time` feature, the time taken does not depend on `e`, see [ct_modpow].
+pub fn secret_modpow(b: &BigUint, e: &BigUint, n: &BigUint) -> BigUint {
+    if cfg!(feature = "constant_time") {
+        backend::secure_modpow(b, e, n)
+    } else {
+        backend::modpow(b, e, n)
+    }
+}
+
+/// Same as [secret_modpow], for a signed exponent. The sign of `e` is not considered secret.
+/// Returns `None` if `e` is negative and `b` is not invertible.
+pub fn secret_modpow_int(b: &BigUint, e: &BigInt, n: &BigUint) -> Option<BigUint> {
+    let e_abs = e.abs().to_biguint().unwrap();
+    if e.is_negative() {
+        backend::mod_inverse(b, n).map(|b_inv| secret_modpow(&b_inv, &e_abs, n))
+    } else {
+        Some(secret_modpow(b, &e_abs, n))
+    }
+}
+
+/// Calculates `a^-1 % n`, for a secret `a`.
+/// With the `constant_time` feature, the inversion is blinded, see [ct_mod_inverse].
+pub fn secret_mod_inverse(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    if cfg!(feature = "constant_time") {
+        ct_mod_inverse(a, n)
+    } else {
+        backend::mod_inverse(a, n)
+    }
+}
+
+/// Calculates the `(xy)`-th root of `g`, given the `x`-th root and `y`-th root of `g.`
+/// Operations are `mod n`.
+/// Returns `None` if the roots don't match or `x` and `y` are not coprime.
+pub fn shamir_trick(
+    root_x: &BigUint,
+    root_y: &BigUint,
+    x: &BigUint,
+    y: &BigUint,
+    n: &BigUint,
+) -> Option<BigUint> {
+    // Check that the roots match to the same element
+    let g1 = backend::modpow(root_x, x, n);
+    let g2 = backend::modpow(root_y, y, n);
+
+    if g1 != g2 {
+        return None;
+    }
+
+    // a, b <- Bezout(x, y)
+    let (gcd, a, b) = backend::extended_gcd(x, y);
+    if !gcd.is_one() {
+        return None;
+    }
+
+    // root_x^b root_y^a
+    multi_modpow_int(&[(root_x, &b), (root_y, &a)], n)
+}
+
+/// Updates the membership witness `w` of `x`, after the elements with product `added` were added
+/// and those with product `deleted` were deleted, resulting in the state `a_new`.
+/// Returns `None` if `x` is not coprime to `deleted`.
+pub fn mem_wit_update(
+    w: &BigUint,
+    x: &BigUint,
+    added: &BigUint,
+    deleted: &BigUint,
+    a_new: &BigUint,
+    n: &BigUint,
+) -> Option<BigUint> {
+    // w^added is a witness for A^added = a_new^deleted
+    let w = backend::modpow(w, added, n);
+    if deleted.is_one() {
+        return Some(w);
+    }
+    if !x.gcd(deleted).is_one() {
+        return None;
+    }
+
+    // the (x deleted)-th root of A^added is the x-th root of a_new
+    shamir_trick(&w, a_new, x, deleted, n)
+}
+
+/// Updates the non-membership witness `(d, b)` of `x`, with `d^x A^b = g`, after the elements with
+/// product `added` were added and those with product `deleted` were deleted, changing the state
+/// from `a_old` to `a_new`.
+/// Returns `None` if `x` is not coprime to `added`.
+pub fn non_mem_wit_update(
+    w: &(BigUint, BigInt),
+    x: &BigUint,
+    added: &BigUint,
+    deleted: &BigUint,
+    a_old: &BigUint,
+    a_new: &BigUint,
+    n: &BigUint,
+) -> Option<(BigUint, BigInt)> {
+    let (d, b) = w;
+
+    // alpha x + beta added = 1, so d A^{b alpha} and b beta are a witness for A^added
+    let (gcd, alpha, beta) = backend::extended_gcd(x, added);
+    if !gcd.is_one() {
+        return None;
+    }
+    let d = (d * modpow_uint_int(a_old, &(b * alpha), n)?) % n;
+
+    // A^added = a_new^deleted
+    let b = b * beta * BigInt::from(deleted.clone());
+
+    // b = q x + r, so d a_new^q and r are a witness for a_new, with r < x
+    let (q, r) = b.div_mod_floor(&BigInt::from(x.clone()));
+    let d = (d * modpow_uint_int(a_new, &q, n)?) % n;
+
+    Some((d, r))
+}
+
+/// Given `y = g^x` and `x = \prod x_i`, calculates the `x_i`-th roots, for all `i`.
+/// All operations are `mod n`.
+pub fn root_factor(g: &BigUint, x: &[BigUint], n: &BigUint) -> Vec<BigUint> {
+    root_factor_fixed_base(g, &FixedBaseTable::default(), x, n)
+}
+
+/// Same as [root_factor], using the precomputed `table` for the exponentiations of `g`.
+/// Falls back to `modpow` for exponents not covered by the table.
+pub fn root_factor_fixed_base(
+    g: &BigUint,
+    table: &FixedBaseTable,
+    x: &[BigUint],
+    n: &BigUint,
+) -> Vec<BigUint> {
+    root_factor_tree(g, table, &ProductTree::new(x), n)
+}
+
+/// Same as [root_factor_fixed_base], using the subtree products of `tree` as exponents.
+pub fn root_factor_tree(
+    g: &BigUint,
+    table: &FixedBaseTable,
+    tree: &ProductTree,
+    n: &BigUint,
+) -> Vec<BigUint> {
+    let (x_l, x_r) = match tree.children() {
+        Some(children) => children,
+        None if tree.is_empty() => return Vec::new(),
+        None => return vec![g.clone()],
+    };
+
+    // both halves are independent, so they can be computed in parallel
+    let g_pow = |p: &BigUint| {
+        table
+            .modpow(p, n)
+            .unwrap_or_else(|| backend::modpow(g, p, n))
+    };
+    // the paper uses the upper part for g_L, and the lower part for g_R
+    let (g_l, g_r) = parallel::join(|| g_pow(x_r.product()), || g_pow(x_l.product()));
+
+    let empty = FixedBaseTable::default();
+    let (mut res, res_r) = parallel::join(
+        || root_factor_tree(&g_l, &empty, x_l, n),
+        || root_factor_tree(&g_r, &empty, x_r, n),
+    );
+    res.extend(res_r);
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::{RandBigInt, RandPrime};
+    use num_traits::{FromPrimitive, Pow};
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_pow_assign_basics() {
+        for i in 0..1024 {
+            for j in 0..128 {
+                let res = BigUint::from_usize(i).unwrap().pow(j as u32);
+                let mut res_big = BigUint::from_usize(i).unwrap();
+                pow_assign(&mut res_big, &BigUint::from_usize(j).unwrap());
+                assert_eq!(res_big, res);
+            }
+        }
+    }
+
+    #[test]
+    fn test_quo_rem() {
+        // Ref: https://www.wolframalpha.com/input/?i=QuotientRemainder%5B-10,+13%5D
+        let (l, r) = &BigInt::from_i64(-10)
+            .unwrap()
+            .div_mod_floor(&BigInt::from_i64(13).unwrap());
+
+        assert_eq!(
+            (l, r),
+            (
+                &BigInt::from_i64(-1).unwrap(),
+                &BigInt::from_i64(3).unwrap(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_modpow() {
+        let cases = vec![["49", "-6193420858199668535", "2881", "6"]];
+
+        for case in &cases {
+            let a = BigUint::parse_bytes(case[0].as_bytes(), 10).unwrap();
+            let e = BigInt::parse_bytes(case[1].as_bytes(), 10).unwrap();
+            let n = BigUint::parse_bytes(case[2].as_bytes(), 10).unwrap();
+            let expected = BigUint::parse_bytes(case[3].as_bytes(), 10).unwrap();
+
+            let actual = modpow_uint_int(&a, &e, &n).unwrap();
+            assert_eq!(expected, actual);
+
+            let actual = secret_modpow_int(&a, &e, &n).unwrap();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_root_factor() {
+        let mut rng = thread_rng();
+
+        for _ in 0..10 {
+            let n = rng.gen_biguint(64);
+            let g = rng.gen_biguint(64);
+            let m: usize = rng.gen_range(1..128);
+
+            let x = (0..m).map(|_| rng.gen_biguint(64)).collect::<Vec<_>>();
+
+            let r = root_factor(&g, &x, &n);
+
+            let mut xs = BigUint::one();
+            for e in &x {
+                xs *= e;
+            }
+            let y = g.modpow(&xs, &n);
+
+            for (root, x_i) in r.iter().zip(x.iter()) {
+                // root is the x_i-th root of y
+                // so we check that root^x_i = y
+                assert_eq!(&root.clone().modpow(x_i, &n), &y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shamir_trick() {
+        let mut rng = thread_rng();
+
+        for _ in 0..30 {
+            let n = rng.gen_biguint(64);
+            let g = rng.gen_prime(64);
+
+            let x = rng.gen_prime(64);
+            let y = rng.gen_prime(64);
+            let z = rng.gen_prime(64);
+
+            // the element we calc the root against
+            let a = g.modpow(&(x.clone() * &y * &z), &n);
+            let root_x = g.modpow(&(y.clone() * &z), &n);
+            let root_y = g.modpow(&(x.clone() * &z), &n);
+
+            // make sure they are actual roots
+            assert_eq!(
+                &root_x.modpow(&x, &n),
+                &a,
+                "root_x is not the x-th root of a"
+            );
+            assert_eq!(
+                &root_y.modpow(&y, &n),
+                &a,
+                "root_y is not the y-th root of a"
+            );
+
+            let root = shamir_trick(&root_x, &root_y, &x, &y, &n).unwrap();
+
+            // root is the xy-th root of a
+            // so we check that root^xy = a
+            assert_eq!(&root.clone().modpow(&(x.clone() * &y), &n), &a);
+        }
+    }
+
+    #[test]
+    fn test_mem_wit_update() {
+        let mut rng = thread_rng();
+
+        for _ in 0..30 {
+            let n = rng.gen_prime(64) * rng.gen_prime(64);
+            let g = rng.gen_prime(64);
+
+            let x = rng.gen_prime(64);
+            let y = rng.gen_prime(64);
+            let z = rng.gen_prime(64);
+            let t = rng.gen_prime(64);
+
+            // {x, z, t} -> {x, t, y}
+            let w = g.modpow(&(&z * &t), &n);
+            let a_new = g.modpow(&(&x * &t * &y), &n);
+
+            let w_new = mem_wit_update(&w, &x, &y, &z, &a_new, &n).unwrap();
+            assert_eq!(w_new.modpow(&x, &n), a_new);
+
+            // x itself was deleted
+            assert!(mem_wit_update(&w, &x, &y, &(&x * &z), &a_new, &n).is_none());
+        }
+    }
+
+    #[test]
+    fn test_non_mem_wit_update() {
+        let mut rng = thread_rng();
+
+        for _ in 0..30 {
+            let n = rng.gen_prime(64) * rng.gen_prime(64);
+            let g = rng.gen_prime(64);
+
+            let x = rng.gen_prime(64);
+            let y = rng.gen_prime(64);
+            let z = rng.gen_prime(64);
+            let t = rng.gen_prime(64);
+
+            // {z, t} -> {t, y}
+            let s = &z * &t;
+            let a_old = g.modpow(&s, &n);
+            let a_new = g.modpow(&(&t * &y), &n);
+
+            let (_, alpha, beta) = backend::extended_gcd(&x, &s);
+            let d = modpow_uint_int(&g, &alpha, &n).unwrap();
+            let w = (d, beta);
+
+            let (d, b) = non_mem_wit_update(&w, &x, &y, &z, &a_old, &a_new, &n).unwrap();
+            assert!(b < BigInt::from(x.clone()));
+            let g_prime = (d.modpow(&x, &n) * modpow_uint_int(&a_new, &b, &n).unwrap()) % &n;
+            assert_eq!(g_prime, g);
+
+            // x itself was added
+            assert!(non_mem_wit_update(&w, &x, &(&x * &y), &z, &a_old, &a_new, &n).is_none());
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+/// Modular arithmetic context, bound to a fixed modulus `n`.
+///
+/// For odd moduli, elements are kept in Montgomery representation `a R mod n`, with `R = 2^(64 k)`,
+/// as `k` limbs, such that multiplications need no divisions and no conversions. The constants
+/// needed for this are computed once, when creating the context. Even moduli fall back to regular
+/// reductions.
+///
+/// Elements must be converted using [MontgomeryContext::to_repr] before, and
+/// [MontgomeryContext::from_repr] after a sequence of operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MontgomeryContext {
+    n: BigUint,
+    params: Option<MontgomeryParams>,
+}
+
+/// An element in the representation of a [MontgomeryContext].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Residue(Repr);
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Repr {
+    /// `a R mod n`, as `k` little endian limbs, for odd moduli.
+    Limbs(Vec<u64>),
+    /// `a mod n`, for even moduli.
+    Plain(BigUint),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct MontgomeryParams {
+    /// Limbs of `n`, little endian.
+    n: Vec<u64>,
+    /// `-n^-1 mod 2^64`
+    n_prime: u64,
+    /// `R^2 mod n`
+    r2: Vec<u64>,
+    /// `R mod n`, the representation of one.
+    one: Vec<u64>,
+}
+
+impl MontgomeryContext {
+    pub fn new(n: &BigUint) -> Self {
+        assert!(!n.is_zero(), "divide by zero!");
+
+        MontgomeryContext {
+            n: n.clone(),
+            params: MontgomeryParams::new(n),
+        }
+    }
+
+    /// Returns the modulus.
+    pub fn modulus(&self) -> &BigUint {
+        &self.n
+    }
+
+    /// Returns the representation of `1`.
+    pub fn one(&self) -> Residue {
+        match self.params {
+            Some(ref params) => Residue(Repr::Limbs(params.one())),
+            None => Residue(Repr::Plain(BigUint::one() % &self.n)),
+        }
+    }
+
+    /// Converts `a` into its representation, `a R mod n`.
+    pub fn to_repr(&self, a: &BigUint) -> Residue {
+        match self.params {
+            Some(ref params) => Residue(Repr::Limbs(params.encode(a))),
+            None => Residue(Repr::Plain(a % &self.n)),
+        }
+    }
+
+    /// Converts the representation `a R mod n` back into `a`.
+    pub fn from_repr(&self, a: &Residue) -> BigUint {
+        match (&self.params, &a.0) {
+            (Some(params), Repr::Limbs(a)) => {
+                let mut one = vec![0; params.len()];
+                one[0] = 1;
+                from_limbs(&params.mul(a, &one))
+            }
+            (None, Repr::Plain(a)) => a.clone(),
+            _ => panic!("element of another context"),
+        }
+    }
+
+    /// Multiplies two representations.
+    pub fn mul(&self, a: &Residue, b: &Residue) -> Residue {
+        match (&self.params, &a.0, &b.0) {
+            (Some(params), Repr::Limbs(a), Repr::Limbs(b)) => {
+                Residue(Repr::Limbs(params.mul(a, b)))
+            }
+            (None, Repr::Plain(a), Repr::Plain(b)) => Residue(Repr::Plain((a * b) % &self.n)),
+            _ => panic!("element of another context"),
+        }
+    }
+
+    /// Squares a representation.
+    pub fn square(&self, a: &Residue) -> Residue {
+        self.mul(a, a)
+    }
+
+    /// Squares a representation `t` times, without allocating for each squaring.
+    pub fn repeated_square(&self, a: &Residue, t: u64) -> Residue {
+        match (&self.params, &a.0) {
+            (Some(params), Repr::Limbs(a)) => {
+                let mut res = a.clone();
+                let mut scratch = vec![0; params.len() + 2];
+                for _ in 0..t {
+                    params.mul_into(&res, &res, &mut scratch);
+                    res.copy_from_slice(&scratch[..params.len()]);
+                }
+
+                Residue(Repr::Limbs(res))
+            }
+            _ => (0..t).fold(a.clone(), |res, _| self.square(&res)),
+        }
+    }
+
+    /// Calculates `a^e % n`, for a representation `a`, returning a representation.
+    pub fn pow(&self, a: &Residue, e: &BigUint) -> Residue {
+        let mut res = self.one();
+        for i in (0..e.bits()).rev() {
+            res = self.square(&res);
+            if bit(e, i) {
+                res = self.mul(&res, a);
+            }
+        }
+
+        res
+    }
+}
+
+impl MontgomeryParams {
+    /// Computes the constants for Montgomery multiplication, which exist for odd moduli only.
+    pub(super) fn new(n: &BigUint) -> Option<Self> {
+        if n.is_even() {
+            return None;
+        }
+
+        let k = limb_count(n);
+        let n_limbs = to_limbs(n, k);
+
+        // n^-1 mod 2^64, using Newton's method, doubling the correct bits each step
+        let mut inv = 1u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n_limbs[0].wrapping_mul(inv)));
+        }
+
+        let r = BigUint::one() << (64 * k);
+        let r2 = (&r * &r) % n;
+
+        Some(MontgomeryParams {
+            n: n_limbs,
+            n_prime: inv.wrapping_neg(),
+            r2: to_limbs(&r2, k),
+            one: to_limbs(&(r % n), k),
+        })
+    }
+
+    /// Returns the limbs of the representation `a R mod n`.
+    pub(super) fn encode(&self, a: &BigUint) -> Vec<u64> {
+        let n = from_limbs(&self.n);
+        self.mul(&to_limbs(&(a % n), self.len()), &self.r2)
+    }
+
+    /// Returns the number of limbs `k` of the modulus.
+    pub(super) fn len(&self) -> usize {
+        self.n.len()
+    }
+
+    /// Converts the limbs of a representation back, in constant time.
+    pub(super) fn decode_ct(&self, a: &[u64]) -> BigUint {
+        let mut one = vec![0; self.len()];
+        one[0] = 1;
+        from_limbs(&self.mul_ct(a, &one))
+    }
+
+    /// Returns the limbs of the representation of `1`.
+    pub(super) fn one(&self) -> Vec<u64> {
+        self.one.clone()
+    }
+
+    /// Montgomery multiplication `a b R^-1 mod n`.
+    /// Inputs and output are `k` limbs, and less than `n`.
+    fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut t = vec![0; self.len() + 2];
+        self.mul_into(a, b, &mut t);
+
+        t.truncate(self.len());
+        t
+    }
+
+    /// Montgomery multiplication into the lower `k` limbs of `t`, which must have `k + 2` limbs.
+    fn mul_into(&self, a: &[u64], b: &[u64], t: &mut [u64]) {
+        self.mul_unreduced(a, b, t);
+        let k = self.len();
+
+        // t < 2n, so a single subtraction is enough
+        if t[k] != 0 || !less_than(&t[..k], &self.n) {
+            sub_assign(&mut t[..k], &self.n);
+        }
+    }
+
+    /// Same as [MontgomeryParams::mul], but without branches or memory accesses depending on
+    /// the values of `a` and `b`.
+    pub(super) fn mul_ct(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let k = self.len();
+        let mut t = vec![0; k + 2];
+        self.mul_unreduced(a, b, &mut t);
+
+        let mut d = t[..k].to_vec();
+        let borrow = sub_assign(&mut d, &self.n);
+
+        // t >= n iff the subtraction does not borrow, or t has an additional high limb
+        let mask = (t[k] | (borrow ^ 1)).wrapping_neg();
+        d.iter()
+            .zip(&t[..k])
+            .map(|(d_j, t_j)| (d_j & mask) | (t_j & !mask))
+            .collect()
+    }
+
+    /// Montgomery multiplication, using the CIOS method, without the final subtraction.
+    /// Writes `k + 1` limbs, less than `2n`, into `t`, which must have `k + 2` limbs.
+    fn mul_unreduced(&self, a: &[u64], b: &[u64], t: &mut [u64]) {
+        let n = &self.n;
+        let k = n.len();
+        for t_j in t.iter_mut() {
+            *t_j = 0;
+        }
+
+        for &b_i in b {
+            // t <- t + a b_i
+            let mut c = 0u64;
+            for j in 0..k {
+                let s = t[j] as u128 + a[j] as u128 * b_i as u128 + c as u128;
+                t[j] = s as u64;
+                c = (s >> 64) as u64;
+            }
+            let s = t[k] as u128 + c as u128;
+            t[k] = s as u64;
+            t[k + 1] = (s >> 64) as u64;
+
+            // t <- (t + m n) / 2^64
+            let m = t[0].wrapping_mul(self.n_prime);
+            let s = t[0] as u128 + m as u128 * n[0] as u128;
+            let mut c = (s >> 64) as u64;
+            for j in 1..k {
+                let s = t[j] as u128 + m as u128 * n[j] as u128 + c as u128;
+                t[j - 1] = s as u64;
+                c = (s >> 64) as u64;
+            }
+            let s = t[k] as u128 + c as u128;
+            t[k - 1] = s as u64;
+            t[k] = t[k + 1] + (s >> 64) as u64;
+        }
+    }
+}
+
+/// Calculates `a -= b` for little endian numbers of the same length, returning the borrow.
+fn sub_assign(a: &mut [u64], b: &[u64]) -> u64 {
+    let mut borrow = 0u64;
+    for (a_j, b_j) in a.iter_mut().zip(b) {
+        let (d, b1) = a_j.overflowing_sub(*b_j);
+        let (d, b2) = d.overflowing_sub(borrow);
+        *a_j = d;
+        borrow = (b1 | b2) as u64;
+    }
+
+    borrow
+}
+
+/// Compares two little endian numbers of the same length.
+fn less_than(a: &[u64], b: &[u64]) -> bool {
+    for (a_i, b_i) in a.iter().zip(b).rev() {
+        if a_i != b_i {
+            return a_i < b_i;
+        }
+    }
+
+    false
+}
+
+pub(super) fn limb_count(n: &BigUint) -> usize {
+    Integer::div_ceil(&n.bits(), &64)
+}
+
+/// Returns the `k` lower limbs of `a`, little endian.
+pub(super) fn to_limbs(a: &BigUint, k: usize) -> Vec<u64> {
+    let len = std::cmp::min(limb_count(a), k);
+    let mut limbs = vec![0; k];
+    for (i, limb) in limbs.iter_mut().enumerate().take(len) {
+        *limb = a.get_limb(i);
+    }
+
+    limbs
+}
+
+fn from_limbs(limbs: &[u64]) -> BigUint {
+    BigUint::from_slice_native(limbs)
+}
+
+fn bit(e: &BigUint, i: usize) -> bool {
+    (e.get_limb(i / 64) >> (i % 64)) & 1 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::RandBigInt;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_montgomery_mul() {
+        let mut rng = thread_rng();
+
+        for bits in &[1, 2, 63, 64, 65, 128, 256, 1000, 2048] {
+            for _ in 0..20 {
+                let n = rng.gen_biguint(*bits) + 1u32;
+                let ctx = MontgomeryContext::new(&n);
+
+                let a = rng.gen_biguint(*bits + 10);
+                let b = rng.gen_biguint(*bits + 10);
+
+                let a_r = ctx.to_repr(&a);
+                let b_r = ctx.to_repr(&b);
+                assert_eq!(ctx.from_repr(&a_r), &a % &n);
+                assert_eq!(ctx.from_repr(&ctx.mul(&a_r, &b_r)), (&a * &b) % &n);
+                assert_eq!(ctx.from_repr(&ctx.square(&a_r)), (&a * &a) % &n);
+                assert_eq!(ctx.from_repr(&ctx.one()), BigUint::one() % &n);
+
+                let mut a_sq = a_r.clone();
+                for t in 0..5 {
+                    assert_eq!(ctx.repeated_square(&a_r, t), a_sq);
+                    a_sq = ctx.square(&a_sq);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_montgomery_mul_ct() {
+        let mut rng = thread_rng();
+
+        for bits in &[64, 65, 256, 1000] {
+            for _ in 0..20 {
+                let n = rng.gen_biguint(*bits) | BigUint::one();
+                let ctx = MontgomeryContext::new(&n);
+                let params = MontgomeryParams::new(&n).unwrap();
+
+                let a_plain = rng.gen_biguint(*bits);
+                let a = params.encode(&a_plain);
+                let b = params.encode(&rng.gen_biguint(*bits));
+
+                assert_eq!(Residue(Repr::Limbs(a.clone())), ctx.to_repr(&a_plain));
+                assert_eq!(params.mul_ct(&a, &b), params.mul(&a, &b));
+                assert_eq!(params.decode_ct(&a), &a_plain % &n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_montgomery_pow() {
+        let mut rng = thread_rng();
+
+        for bits in &[64, 128, 256, 1024] {
+            for _ in 0..10 {
+                let n = rng.gen_biguint(*bits) + 1u32;
+                let ctx = MontgomeryContext::new(&n);
+
+                let a = rng.gen_biguint(*bits);
+                let e = rng.gen_biguint(300);
+
+                let res = ctx.from_repr(&ctx.pow(&ctx.to_repr(&a), &e));
+                assert_eq!(res, a.modpow(&e, &n));
+            }
+        }
+    }
+}
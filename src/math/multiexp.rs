@@ -0,0 +1,322 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use super::{backend, MontgomeryContext, Residue};
+use crate::parallel;
+
+/// Minimum number of pairs per chunk, when splitting a multi-exponentiation across threads.
+const MIN_CHUNK_SIZE: usize = 8;
+
+/// Calculates `\prod b_i^{e_i} % n`, for all pairs `(b_i, e_i)`.
+/// The squarings are shared between all terms (Straus' method), so this is cheaper than
+/// exponentiating each base separately.
+pub fn multi_modpow(pairs: &[(&BigUint, &BigUint)], n: &BigUint) -> BigUint {
+    let ctx = MontgomeryContext::new(n);
+    let products = parallel::map(&chunks(pairs), |chunk| {
+        let terms = chunk
+            .iter()
+            .map(|(b, e)| Term::unsigned(b, e, &ctx))
+            .collect::<Vec<_>>();
+
+        eval_terms(&terms, &ctx)
+    });
+
+    ctx.from_repr(&product(&products, &ctx))
+}
+
+/// Calculates `\prod b_i^{e_i} % n`, for all pairs `(b_i, e_i)`, with signed exponents.
+/// Returns `None` if an exponent is negative and the base is not invertible.
+///
+/// Exponents are recoded into signed digits (wNAF), when the inverse of the base is known.
+pub fn multi_modpow_int(pairs: &[(&BigUint, &BigInt)], n: &BigUint) -> Option<BigUint> {
+    let ctx = MontgomeryContext::new(n);
+    let products = parallel::map(&chunks(pairs), |chunk| {
+        let terms = chunk
+            .iter()
+            .map(|(b, e)| Term::signed(b, e, &ctx))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(eval_terms(&terms, &ctx))
+    });
+
+    Some(ctx.from_repr(&product(
+        &products.into_iter().collect::<Option<Vec<_>>>()?,
+        &ctx,
+    )))
+}
+
+/// Splits the pairs into one chunk per thread, the results of which are multiplied at the end.
+fn chunks<T>(pairs: &[T]) -> Vec<&[T]> {
+    let count = std::cmp::max(
+        1,
+        std::cmp::min(parallel::num_chunks(), pairs.len() / MIN_CHUNK_SIZE),
+    );
+    let size = Integer::div_ceil(&pairs.len(), &count);
+    if size == 0 {
+        return vec![pairs];
+    }
+
+    pairs.chunks(size).collect()
+}
+
+/// Multiplies the results of all chunks.
+fn product(products: &[Residue], ctx: &MontgomeryContext) -> Residue {
+    products.iter().fold(ctx.one(), |acc, p| ctx.mul(&acc, p))
+}
+
+/// A single base, with its exponent recoded into odd digits in `(-2^w, 2^w)`.
+struct Term {
+    /// Little endian digits.
+    digits: Vec<i32>,
+    /// `b^1, b^3, b^5, ...`, in Montgomery representation
+    pos: Vec<Residue>,
+    /// `b^-1, b^-3, b^-5, ...`, in Montgomery representation
+    neg: Vec<Residue>,
+}
+
+impl Term {
+    /// Recodes into unsigned digits, so no inverse of `b` is needed.
+    fn unsigned(b: &BigUint, e: &BigUint, ctx: &MontgomeryContext) -> Self {
+        let w = window(e.bits());
+        let digits = recode(e, w, false);
+
+        Term {
+            digits,
+            pos: odd_powers(b, 1 << (w - 1), ctx),
+            neg: Vec::new(),
+        }
+    }
+
+    /// Recodes into signed digits, falling back to unsigned digits if `b` is not invertible.
+    fn signed(b: &BigUint, e: &BigInt, ctx: &MontgomeryContext) -> Option<Self> {
+        let e_abs = e.abs().to_biguint().unwrap();
+        let b_inv = backend::mod_inverse(b, ctx.modulus());
+
+        let (b, b_inv) = match (e.sign(), b_inv) {
+            (Sign::Minus, None) => return None,
+            // b^-e = (b^-1)^e
+            (Sign::Minus, Some(b_inv)) => (b_inv, b.clone()),
+            (_, None) => return Some(Term::unsigned(b, &e_abs, ctx)),
+            (_, Some(b_inv)) => (b.clone(), b_inv),
+        };
+
+        let w = window(e_abs.bits());
+        let digits = recode(&e_abs, w, true);
+        let size = 1 << (w - 2);
+
+        Some(Term {
+            digits,
+            pos: odd_powers(&b, size, ctx),
+            neg: odd_powers(&b_inv, size, ctx),
+        })
+    }
+}
+
+/// Window size, depending on the size of the exponent.
+fn window(bits: usize) -> usize {
+    match bits {
+        0..=32 => 2,
+        33..=128 => 3,
+        129..=512 => 4,
+        _ => 5,
+    }
+}
+
+/// Calculates `b, b^3, b^5, ...`, the first `count` odd powers of `b`, in Montgomery representation.
+fn odd_powers(b: &BigUint, count: usize, ctx: &MontgomeryContext) -> Vec<Residue> {
+    let mut powers = Vec::with_capacity(count);
+    let b = ctx.to_repr(b);
+    let b_sq = ctx.square(&b);
+
+    powers.push(b);
+    for i in 1..count {
+        let next = ctx.mul(&powers[i - 1], &b_sq);
+        powers.push(next);
+    }
+
+    powers
+}
+
+/// Recodes `e` into odd digits of width `w`, or zero.
+/// Signed digits are in `(-2^(w-1), 2^(w-1))` (wNAF), unsigned digits in `[0, 2^w)`.
+fn recode(e: &BigUint, w: usize, signed: bool) -> Vec<i32> {
+    let modulus = 1i32 << w;
+    let mut k = e.clone();
+    let mut digits = Vec::with_capacity(e.bits() + 1);
+
+    while !k.is_zero() {
+        let d = if k.is_odd() {
+            let mut d = (&k % modulus as u32).to_i32().unwrap();
+            if signed && d >= modulus / 2 {
+                d -= modulus;
+            }
+
+            if d >= 0 {
+                k -= d as u32;
+            } else {
+                k += (-d) as u32;
+            }
+
+            d
+        } else {
+            0
+        };
+
+        digits.push(d);
+        k >>= 1;
+    }
+
+    digits
+}
+
+fn eval_terms(terms: &[Term], ctx: &MontgomeryContext) -> Residue {
+    let len = terms.iter().map(|t| t.digits.len()).max().unwrap_or(0);
+
+    let mut res = ctx.one();
+    for i in (0..len).rev() {
+        res = ctx.square(&res);
+
+        for term in terms {
+            match term.digits.get(i) {
+                Some(&d) if d > 0 => res = ctx.mul(&res, &term.pos[(d / 2) as usize]),
+                Some(&d) if d < 0 => res = ctx.mul(&res, &term.neg[(-d / 2) as usize]),
+                _ => {}
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::math::modpow_uint_int;
+    use num_bigint::{RandBigInt, RandPrime};
+    use num_traits::One;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_recode() {
+        let mut rng = thread_rng();
+
+        for _ in 0..100 {
+            let bits = rng.gen_range(1..600);
+            let e = rng.gen_biguint(bits);
+
+            for w in 2..6 {
+                for &signed in &[false, true] {
+                    let digits = recode(&e, w, signed);
+
+                    let mut res = BigInt::zero();
+                    for d in digits.iter().rev() {
+                        assert!(*d == 0 || d % 2 != 0, "digits must be odd or zero");
+                        res = (res << 1) + BigInt::from(*d);
+                    }
+                    assert_eq!(res, BigInt::from_biguint(Sign::Plus, e.clone()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_modpow() {
+        let mut rng = thread_rng();
+
+        for _ in 0..30 {
+            let n = rng.gen_biguint(128);
+            let m: usize = rng.gen_range(0..8);
+
+            let bases = (0..m).map(|_| rng.gen_biguint(128)).collect::<Vec<_>>();
+            let exps = (0..m)
+                .map(|_| {
+                    let bits = rng.gen_range(1..700);
+                    rng.gen_biguint(bits)
+                })
+                .collect::<Vec<_>>();
+
+            let mut expected = BigUint::one() % &n;
+            for (b, e) in bases.iter().zip(&exps) {
+                expected = (expected * b.modpow(e, &n)) % &n;
+            }
+
+            let pairs = bases.iter().zip(&exps).collect::<Vec<_>>();
+            assert_eq!(multi_modpow(&pairs, &n), expected);
+        }
+    }
+
+    #[test]
+    fn test_multi_modpow_chunks() {
+        let mut rng = thread_rng();
+
+        for _ in 0..5 {
+            let n = rng.gen_biguint(256);
+            let m: usize = rng.gen_range(50..200);
+
+            let bases = (0..m).map(|_| rng.gen_biguint(256)).collect::<Vec<_>>();
+            let exps = (0..m).map(|_| rng.gen_biguint(128)).collect::<Vec<_>>();
+            let pairs = bases.iter().zip(&exps).collect::<Vec<_>>();
+
+            // a single chunk, as without the `parallel` feature
+            let ctx = MontgomeryContext::new(&n);
+            let terms = pairs
+                .iter()
+                .map(|(b, e)| Term::unsigned(b, e, &ctx))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                multi_modpow(&pairs, &n),
+                ctx.from_repr(&eval_terms(&terms, &ctx))
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_modpow_int() {
+        let mut rng = thread_rng();
+
+        for _ in 0..30 {
+            let n = rng.gen_prime(128) * rng.gen_prime(128);
+            let m: usize = rng.gen_range(0..8);
+
+            let bases = (0..m).map(|_| rng.gen_biguint(256)).collect::<Vec<_>>();
+            let exps = (0..m)
+                .map(|_| {
+                    let bits = rng.gen_range(1..700);
+                    let e: BigInt = rng.gen_biguint(bits).into();
+                    if rng.gen() {
+                        -e
+                    } else {
+                        e
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut expected = BigUint::one() % &n;
+            for (b, e) in bases.iter().zip(&exps) {
+                expected = (expected * modpow_uint_int(b, e, &n).unwrap()) % &n;
+            }
+
+            let pairs = bases.iter().zip(&exps).collect::<Vec<_>>();
+            assert_eq!(multi_modpow_int(&pairs, &n), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_multi_modpow_int_not_invertible() {
+        let mut rng = thread_rng();
+
+        let p = rng.gen_prime(128);
+        let n = &p * rng.gen_prime(128);
+        let b = p * 3u32;
+        let e: BigInt = rng.gen_biguint(256).into();
+
+        assert_eq!(
+            multi_modpow_int(&[(&b, &e)], &n),
+            Some(modpow_uint_int(&b, &e, &n).unwrap())
+        );
+        assert_eq!(multi_modpow_int(&[(&b, &-e)], &n), None);
+    }
+}
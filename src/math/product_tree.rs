@@ -0,0 +1,150 @@
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::parallel;
+
+/// Calculates `\prod x_i`, multiplying balanced halves, such that the operands of each
+/// multiplication have about the same size.
+pub fn product(xs: &[BigUint]) -> BigUint {
+    match xs.len() {
+        0 => BigUint::one(),
+        1 => xs[0].clone(),
+        m => {
+            let (x_l, x_r) = xs.split_at(m / 2);
+            let (p_l, p_r) = parallel::join(|| product(x_l), || product(x_r));
+
+            p_l * p_r
+        }
+    }
+}
+
+/// A binary tree of the products of all contiguous halves of a list of integers.
+///
+/// A node over `x_0, .., x_{m-1}` has the product of all `x_i` and, for `m > 1`, the children
+/// over `x_0, .., x_{m/2-1}` and `x_{m/2}, .., x_{m-1}`. The leaves are the integers themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductTree {
+    product: BigUint,
+    len: usize,
+    children: Option<Box<(ProductTree, ProductTree)>>,
+}
+
+impl ProductTree {
+    pub fn new(xs: &[BigUint]) -> Self {
+        match xs.len() {
+            0 => ProductTree {
+                product: BigUint::one(),
+                len: 0,
+                children: None,
+            },
+            1 => ProductTree {
+                product: xs[0].clone(),
+                len: 1,
+                children: None,
+            },
+            m => {
+                let (x_l, x_r) = xs.split_at(m / 2);
+                let (left, right) =
+                    parallel::join(|| ProductTree::new(x_l), || ProductTree::new(x_r));
+
+                ProductTree {
+                    product: &left.product * &right.product,
+                    len: m,
+                    children: Some(Box::new((left, right))),
+                }
+            }
+        }
+    }
+
+    /// Returns the product of all integers in this subtree.
+    pub fn product(&self) -> &BigUint {
+        &self.product
+    }
+
+    /// Returns the number of integers in this subtree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the subtrees over the lower and the upper half, if there is more than one integer.
+    pub fn children(&self) -> Option<(&ProductTree, &ProductTree)> {
+        self.children
+            .as_ref()
+            .map(|children| (&children.0, &children.1))
+    }
+
+    /// Calculates `y mod x_i`, for all `i`, reducing `y` by the subtree products on the way down
+    /// (remainder tree).
+    pub fn remainders(&self, y: &BigUint) -> Vec<BigUint> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let y = y % &self.product;
+        match self.children() {
+            None => vec![y],
+            Some((left, right)) => {
+                let (mut res, res_r) =
+                    parallel::join(|| left.remainders(&y), || right.remainders(&y));
+                res.extend(res_r);
+
+                res
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::RandBigInt;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_product() {
+        let mut rng = thread_rng();
+
+        for m in 0..40 {
+            let xs = (0..m)
+                .map(|_| rng.gen_biguint(128) + 1u32)
+                .collect::<Vec<_>>();
+
+            let mut expected = BigUint::one();
+            for x in &xs {
+                expected *= x;
+            }
+
+            assert_eq!(product(&xs), expected);
+
+            let tree = ProductTree::new(&xs);
+            assert_eq!(tree.product(), &expected);
+            assert_eq!(tree.len(), m);
+
+            if let Some((left, right)) = tree.children() {
+                assert_eq!(left.len(), m / 2);
+                assert_eq!(left.product() * right.product(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_remainders() {
+        let mut rng = thread_rng();
+
+        for _ in 0..20 {
+            let m: usize = rng.gen_range(0..40);
+            let xs = (0..m)
+                .map(|_| rng.gen_biguint(64) + 1u32)
+                .collect::<Vec<_>>();
+            let y = rng.gen_biguint(4096);
+
+            let expected = xs.iter().map(|x| &y % x).collect::<Vec<_>>();
+            assert_eq!(ProductTree::new(&xs).remainders(&y), expected);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Helpers to run independent work across threads, with the `parallel` feature enabled.
+//!
+//! The bounds are [MaybeSend] and [MaybeSync], which only require `Send` and `Sync` with the
+//! feature enabled. Results are always returned in order, so outputs do not depend on whether
+//! the feature is enabled.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::traits::{MaybeSend, MaybeSync};
+
+/// Runs `a` and `b`, potentially in parallel, returning both results.
+#[cfg(feature = "parallel")]
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + MaybeSend,
+    B: FnOnce() -> RB + MaybeSend,
+    RA: MaybeSend,
+    RB: MaybeSend,
+{
+    rayon::join(a, b)
+}
+
+/// Runs `a` and `b`, potentially in parallel, returning both results.
+#[cfg(not(feature = "parallel"))]
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + MaybeSend,
+    B: FnOnce() -> RB + MaybeSend,
+    RA: MaybeSend,
+    RB: MaybeSend,
+{
+    (a(), b())
+}
+
+/// Applies `f` to all items, potentially in parallel, returning the results in order.
+#[cfg(feature = "parallel")]
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: MaybeSync,
+    R: MaybeSend,
+    F: Fn(&T) -> R + MaybeSync + MaybeSend,
+{
+    items.par_iter().map(f).collect()
+}
+
+/// Applies `f` to all items, potentially in parallel, returning the results in order.
+#[cfg(not(feature = "parallel"))]
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: MaybeSync,
+    R: MaybeSend,
+    F: Fn(&T) -> R + MaybeSync + MaybeSend,
+{
+    items.iter().map(f).collect()
+}
+
+/// Returns the number of chunks work should be split into.
+#[cfg(feature = "parallel")]
+pub fn num_chunks() -> usize {
+    rayon::current_num_threads()
+}
+
+/// Returns the number of chunks work should be split into.
+#[cfg(not(feature = "parallel"))]
+pub fn num_chunks() -> usize {
+    1
+}
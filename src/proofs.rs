@@ -1,8 +1,15 @@
+use std::collections::BTreeMap;
+
 use crate::hash::{hash_group, hash_prime};
-use crate::math::modpow_uint_int;
+use crate::math::{
+    backend, modpow_uint_int, multi_modpow, multi_modpow_int, secret_modpow, secret_modpow_int,
+};
+use crate::parallel;
 use blake2::{Blake2b512, Digest};
-use num_bigint::{BigInt, BigUint};
+use byteorder::{BigEndian, WriteBytesExt};
+use num_bigint::{BigInt, BigUint, Sign};
 use num_integer::Integer;
+use num_traits::{One, Signed};
 
 // Let G be a group of unknown order.
 // Here both the prover and verifier are given (u, w, x) and
@@ -12,24 +19,125 @@ pub type ExponentProof = BigUint;
 
 pub type KnowledgeProof = (BigUint, BigUint, BigInt);
 
+/// A NI-PoE instance `(x, u, w, Q)`, claiming `u^x = w` with proof `Q`.
+pub type ExponentInstance<'a> = (&'a BigUint, &'a BigUint, &'a BigUint, &'a ExponentProof);
+
+/// A NI-PoKE2 instance `(u, w, pi)`, claiming knowledge of `x` with `u^x = w`.
+pub type KnowledgeInstance<'a> = (&'a BigUint, &'a BigUint, &'a KnowledgeProof);
+
+/// Size of the random coefficients used for batch verification, in bytes.
+const BATCH_COEFFICIENT_BYTES: usize = 16;
+
+/// NI-PoKE proof `(z, Q, Q', r)`, with `z = g^x`, `Q = u^q` and `Q' = g^q`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoKEProof {
+    pub z: BigUint,
+    pub q: BigUint,
+    pub q_g: BigUint,
+    pub r: BigUint,
+}
+
+/// NI-PoKCR proof, the product of all roots.
+pub type CoprimeRootsProof = BigUint;
+
+/// NI-PoDDH proof `(Q_a, Q_b, Q_c, r_a, r_b)`, with `Q_a = g^{q_a}`, `Q_b = g^{q_b}` and `Q_c = y_2^{q_a}`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoDDHProof {
+    pub q_a: BigUint,
+    pub q_b: BigUint,
+    pub q_c: BigUint,
+    pub r_a: BigUint,
+    pub r_b: BigUint,
+}
+
+/// Fiat-Shamir transcript.
+/// Challenges are derived by hashing the concatenation of all values appended so far, each
+/// prefixed with its length, such that different sequences of values never collide.
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    to_hash: Vec<u8>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the length prefixed big endian encoding of `value` to the transcript.
+    pub fn append(&mut self, value: &BigUint) -> &mut Self {
+        let bytes = value.to_bytes_be();
+        self.to_hash
+            .write_u64::<BigEndian>(bytes.len() as u64)
+            .expect("writing to a vec");
+        self.to_hash.extend(&bytes);
+        self
+    }
+
+    /// H_prime(transcript)
+    pub fn challenge_prime(&self) -> BigUint {
+        hash_prime::<_, Blake2b512>(&self.to_hash)
+    }
+
+    /// H_G(transcript)
+    pub fn challenge_group(&self, n: &BigUint) -> BigUint {
+        hash_group::<_, Blake2b512>(&self.to_hash, n)
+    }
+
+    /// H(transcript)
+    pub fn challenge(&self) -> BigUint {
+        BigUint::from_bytes_be(&Blake2b512::digest(&self.to_hash)[..])
+    }
+
+    /// H(transcript), truncated to the given number of bytes.
+    pub fn challenge_bytes(&self, len: usize) -> BigUint {
+        let digest = Blake2b512::digest(&self.to_hash);
+        let len = std::cmp::min(digest.len(), len);
+
+        BigUint::from_bytes_be(&digest[..len])
+    }
+}
+
+/// l <- H_prime(x, u, w)
+fn poe_challenge(x: &BigUint, u: &BigUint, w: &BigUint) -> BigUint {
+    Transcript::new()
+        .append(x)
+        .append(u)
+        .append(w)
+        .challenge_prime()
+}
+
+/// g <- H_G(u, w)
+fn poke2_group_element(u: &BigUint, w: &BigUint, n: &BigUint) -> BigUint {
+    Transcript::new().append(u).append(w).challenge_group(n)
+}
+
+/// l <- H_prime(u, w, z)
+/// alpha <- H(u, w, z, l)
+fn poke2_challenge(u: &BigUint, w: &BigUint, z: &BigUint) -> (BigUint, BigUint) {
+    let mut transcript = Transcript::new();
+    transcript.append(u).append(w).append(z);
+    let l = transcript.challenge_prime();
+    let alpha = transcript.append(&l).challenge();
+
+    (l, alpha)
+}
+
 /// NI-PoE Prove
 /// Assumes `u^x = w`
 /// All operations are `mod n`.
 pub fn ni_poe_prove(x: &BigUint, u: &BigUint, w: &BigUint, n: &BigUint) -> ExponentProof {
-    debug_assert!(&u.modpow(x, n) == w, "invalid input");
+    debug_assert!(&backend::modpow(u, x, n) == w, "invalid input");
 
     // l <- H_prime(x, u, w)
-    let mut to_hash = x.to_bytes_be();
-    to_hash.extend(&u.to_bytes_be());
-    to_hash.extend(&w.to_bytes_be());
-
-    let l = hash_prime::<_, Blake2b512>(&to_hash);
+    let l = poe_challenge(x, u, w);
 
     // q <- floor(x/l)
     let q = x.div_floor(&l);
 
     //Prover sends Q <- u^q ∈ G to the Verifier.
-    u.modpow(&q, n)
+    backend::modpow(u, &q, n)
 }
 
 /// NI-PoE Verify
@@ -43,17 +151,13 @@ pub fn ni_poe_verify(
     n: &BigUint,
 ) -> bool {
     // l <- H_prime(x, u, w)
-    let mut to_hash = x.to_bytes_be();
-    to_hash.extend(&u.to_bytes_be());
-    to_hash.extend(&w.to_bytes_be());
-
-    let l = hash_prime::<_, Blake2b512>(&to_hash);
+    let l = poe_challenge(x, u, w);
 
     // r <- x mod l
     let r = x.mod_floor(&l);
 
     // Q^l u^r == w
-    &((q.modpow(&l, n) * &u.modpow(&r, n)) % n) == w
+    &multi_modpow(&[(q, &l), (u, &r)], n) == w
 }
 
 //proof of knowledge of exponent, i.e. a proof that a computationally bounded prover knows the discrete logarithm between two elements in a group of unknown order. The proof is succinct in that the proof size and verification time is independent of the size of the discrete-log.
@@ -72,27 +176,23 @@ pub fn ni_poke2_prove(
     debug_assert!(&modpow_uint_int(u, &x, n).unwrap() == w, "invalid input");
 
     // g <- H_G(u, w)
-    let mut to_hash = u.to_bytes_be();
-    to_hash.extend(&w.to_bytes_be());
-    let g = hash_group::<_, Blake2b512>(&to_hash, n);
+    let g = poke2_group_element(u, w, n);
 
     // z = g^x
-    let z = modpow_uint_int(&g, &x, n).expect("invalid state");
+    let z = secret_modpow_int(&g, &x, n).expect("invalid state");
 
     // l <- H_prime(u, w, z)
-    to_hash.extend(&z.to_bytes_be());
-    let l: BigInt = hash_prime::<_, Blake2b512>(&to_hash).into();
-
     // alpha = H(u, w, z, l)
-    to_hash.extend(&l.to_bytes_be().1);
-    let alpha = BigUint::from_bytes_be(&Blake2b512::digest(&to_hash)[..]);
+    let (l, alpha) = poke2_challenge(u, w, &z);
+    let l: BigInt = l.into();
 
     // q <- floor(x/l)
     // r <- x % l
     let (q, r) = x.div_rem(&l);
 
     // Q <- (ug^alpha)^q
-    let q_big = modpow_uint_int(&(u * &g.modpow(&alpha, n)), &q, n).expect("invalid state");
+    let q_big =
+        secret_modpow_int(&(u * &backend::modpow(&g, &alpha, n)), &q, n).expect("invalid state");
 
     (z, q_big, r)
 }
@@ -110,39 +210,390 @@ pub fn ni_poke2_verify(
     let (z, q_big, r) = pi;
 
     // g <- H_G(u, w)
-    let mut to_hash = u.to_bytes_be();
-    to_hash.extend(&w.to_bytes_be());
-    let g = hash_group::<_, Blake2b512>(&to_hash, n);
+    let g = poke2_group_element(u, w, n);
 
     // l <- H_prime(u, w, z)
-    to_hash.extend(&z.to_bytes_be());
-    let l = hash_prime::<_, Blake2b512>(&to_hash);
-
     // alpha = H(u, w, z, l)
-    to_hash.extend(&l.to_bytes_be());
-    let alpha = BigUint::from_bytes_be(&Blake2b512::digest(&to_hash)[..]);
+    let (l, alpha) = poke2_challenge(u, w, z);
 
-    // Q^l(ug^alpha)^r
-    let lhs: BigInt = ((q_big.modpow(&l, n)
-        * modpow_uint_int(&(u * &g.modpow(&alpha, n)), r, n).expect("invalid state"))
-        % n)
-        .into();
+    // Q^l(ug^alpha)^r = Q^l u^r g^{alpha r}
+    let l: BigInt = l.into();
+    let alpha_r = BigInt::from_biguint(Sign::Plus, alpha.clone()) * r;
+    let lhs = match multi_modpow_int(&[(q_big, &l), (u, r), (&g, &alpha_r)], n) {
+        Some(lhs) => lhs,
+        None => return false,
+    };
 
     // wz^alpha
-    let z_alpha = z.modpow(&alpha, n);
-    let rhs: BigInt = ((w * z_alpha) % n).into();
+    let rhs = multi_modpow(&[(w, &BigUint::one()), (z, &alpha)], n);
 
     lhs == rhs
 }
 
+/// NI-PoKE Prove
+/// Proof of knowledge of exponent, for an arbitrary base `u`, relative to the fixed base `g`.
+/// Assumes `u^x = w`
+/// All operations are `mod n`.
+pub fn ni_poke_prove(x: &BigUint, g: &BigUint, u: &BigUint, w: &BigUint, n: &BigUint) -> PoKEProof {
+    debug_assert!(&backend::modpow(u, x, n) == w, "invalid input");
+
+    // z <- g^x
+    let z = secret_modpow(g, x, n);
+
+    // l <- H_prime(g, u, w, z)
+    let l = Transcript::new()
+        .append(g)
+        .append(u)
+        .append(w)
+        .append(&z)
+        .challenge_prime();
+
+    // q <- floor(x/l)
+    // r <- x % l
+    let (q, r) = x.div_rem(&l);
+
+    // Q <- u^q, Q' <- g^q
+    PoKEProof {
+        z,
+        q: secret_modpow(u, &q, n),
+        q_g: secret_modpow(g, &q, n),
+        r,
+    }
+}
+
+/// NI-PoKE Verify
+/// Assumes `u^x = w`
+/// All operations are `mod n`.
+pub fn ni_poke_verify(g: &BigUint, u: &BigUint, w: &BigUint, pi: &PoKEProof, n: &BigUint) -> bool {
+    let PoKEProof { z, q, q_g, r } = pi;
+
+    // l <- H_prime(g, u, w, z)
+    let l = Transcript::new()
+        .append(g)
+        .append(u)
+        .append(w)
+        .append(z)
+        .challenge_prime();
+
+    // r \in [l]
+    if r >= &l {
+        return false;
+    }
+
+    // Q^l u^r == w
+    if &multi_modpow(&[(q, &l), (u, r)], n) != w {
+        return false;
+    }
+
+    // Q'^l g^r == z
+    &multi_modpow(&[(q_g, &l), (g, r)], n) == z
+}
+
+/// NI-PoKCR Prove
+/// Proof of knowledge of co-prime roots, given the roots `w_i`, such that `w_i^{x_i} = alpha_i`.
+/// All operations are `mod n`.
+pub fn ni_pokcr_prove(ws: &[BigUint], n: &BigUint) -> CoprimeRootsProof {
+    // w <- \prod w_i
+    let mut w = BigUint::one() % n;
+    for w_i in ws {
+        w = (w * w_i) % n;
+    }
+
+    w
+}
+
+/// NI-PoKCR Verify
+/// Verifies knowledge of `w_i`, such that `w_i^{x_i} = alpha_i`, for pairwise co-prime `x_i`.
+/// All operations are `mod n`.
+pub fn ni_pokcr_verify(
+    alphas: &[BigUint],
+    xs: &[BigUint],
+    w: &CoprimeRootsProof,
+    n: &BigUint,
+) -> bool {
+    if alphas.len() != xs.len() {
+        return false;
+    }
+
+    // x* <- \prod x_i
+    let mut x_star = BigUint::one();
+    for x in xs {
+        if !x.gcd(&x_star).is_one() {
+            // not pairwise co-prime
+            return false;
+        }
+        x_star *= x;
+    }
+
+    // w^{x*} == \prod alpha_i^{x*/x_i}
+    backend::modpow(w, &x_star, n) == coprime_roots_product(alphas, xs, n).0
+}
+
+/// Calculates `(\prod alpha_i^{x*/x_i}, x*)`, splitting the set in halves, to avoid
+/// computing `x*/x_i` for each `i`.
+fn coprime_roots_product(alphas: &[BigUint], xs: &[BigUint], n: &BigUint) -> (BigUint, BigUint) {
+    match alphas.len() {
+        0 => (BigUint::one() % n, BigUint::one()),
+        1 => (alphas[0].clone() % n, xs[0].clone()),
+        m => {
+            let (alphas_l, alphas_r) = alphas.split_at(m / 2);
+            let (xs_l, xs_r) = xs.split_at(m / 2);
+
+            let (a_l, x_l) = coprime_roots_product(alphas_l, xs_l, n);
+            let (a_r, x_r) = coprime_roots_product(alphas_r, xs_r, n);
+
+            // a_l^{x_r} a_r^{x_l}
+            let a = multi_modpow(&[(&a_l, &x_r), (&a_r, &x_l)], n);
+
+            (a, x_l * x_r)
+        }
+    }
+}
+
+/// NI-PoDDH Prove
+/// Proves that `(y_1, y_2, y_3) = (g^a, g^b, g^{ab})`.
+/// All operations are `mod n`.
+///
+/// Note: The proof reveals `a mod l` and `b mod l`, so it is not zero-knowledge.
+pub fn ni_poddh_prove(
+    a: &BigUint,
+    b: &BigUint,
+    g: &BigUint,
+    y1: &BigUint,
+    y2: &BigUint,
+    y3: &BigUint,
+    n: &BigUint,
+) -> PoDDHProof {
+    debug_assert!(&backend::modpow(g, a, n) == y1, "invalid input");
+    debug_assert!(&backend::modpow(g, b, n) == y2, "invalid input");
+    debug_assert!(&backend::modpow(y2, a, n) == y3, "invalid input");
+
+    // l <- H_prime(g, y_1, y_2, y_3)
+    let l = Transcript::new()
+        .append(g)
+        .append(y1)
+        .append(y2)
+        .append(y3)
+        .challenge_prime();
+
+    let (q_a, r_a) = a.div_rem(&l);
+    let (q_b, r_b) = b.div_rem(&l);
+
+    PoDDHProof {
+        q_a: secret_modpow(g, &q_a, n),
+        q_b: secret_modpow(g, &q_b, n),
+        q_c: secret_modpow(y2, &q_a, n),
+        r_a,
+        r_b,
+    }
+}
+
+/// NI-PoDDH Verify
+/// Verifies that `(y_1, y_2, y_3) = (g^a, g^b, g^{ab})`.
+/// All operations are `mod n`.
+pub fn ni_poddh_verify(
+    g: &BigUint,
+    y1: &BigUint,
+    y2: &BigUint,
+    y3: &BigUint,
+    pi: &PoDDHProof,
+    n: &BigUint,
+) -> bool {
+    let PoDDHProof {
+        q_a,
+        q_b,
+        q_c,
+        r_a,
+        r_b,
+    } = pi;
+
+    // l <- H_prime(g, y_1, y_2, y_3)
+    let l = Transcript::new()
+        .append(g)
+        .append(y1)
+        .append(y2)
+        .append(y3)
+        .challenge_prime();
+
+    // r_a, r_b \in [l]
+    if r_a >= &l || r_b >= &l {
+        return false;
+    }
+
+    // Q_a^l g^{r_a} == y_1
+    if &multi_modpow(&[(q_a, &l), (g, r_a)], n) != y1 {
+        return false;
+    }
+
+    // Q_b^l g^{r_b} == y_2
+    if &multi_modpow(&[(q_b, &l), (g, r_b)], n) != y2 {
+        return false;
+    }
+
+    // Q_c^l y_2^{r_a} == y_3
+    &multi_modpow(&[(q_c, &l), (y2, r_a)], n) == y3
+}
+
+/// NI-PoE Batch Verify
+/// Verifies all instances at once, by checking a random linear combination of their
+/// verification equations `Q^l u^r = w`, with coefficients derived from all instances.
+/// Returns the index of the first invalid proof, if the batch is rejected.
+/// All operations are `mod n`.
+///
+/// Note: Like all checks in `Z_n^*`, this does not detect errors of small order (e.g. `-1`),
+/// which can cancel out between instances.
+pub fn ni_poe_batch_verify(instances: &[ExponentInstance], n: &BigUint) -> Result<(), usize> {
+    // ni_poe_verify compares with w itself, so unreduced w are invalid, but would be reduced
+    // by the combination
+    if let Some(i) = instances.iter().position(|(_, _, w, _)| *w >= n) {
+        return Err(i);
+    }
+
+    let mut transcript = Transcript::new();
+    for (x, u, w, q) in instances {
+        transcript.append(x).append(u).append(w).append(q);
+    }
+    let cs = batch_coefficients(&transcript, instances.len());
+
+    let mut lhs = BTreeMap::new();
+    let mut rhs = BTreeMap::new();
+
+    let challenges = parallel::map(instances, |(x, u, w, _)| {
+        // l <- H_prime(x, u, w)
+        let l = poe_challenge(x, u, w);
+        // r <- x mod l
+        let r = x.mod_floor(&l);
+
+        (l, r)
+    });
+
+    for (((_, u, w, q), c), (l, r)) in instances.iter().zip(&cs).zip(challenges) {
+        // (Q^l u^r)^c == w^c
+        add_batch_term(&mut lhs, q, c * &l);
+        add_batch_term(&mut lhs, u, c * &r);
+        add_batch_term(&mut rhs, w, c.clone());
+    }
+
+    if eval_batch_terms(&lhs, n) == eval_batch_terms(&rhs, n) {
+        return Ok(());
+    }
+
+    find_invalid(instances, |(x, u, w, q)| ni_poe_verify(x, u, w, q, n))
+}
+
+/// NI-PoKE2 Batch Verify
+/// Verifies all instances at once, by checking a random linear combination of their
+/// verification equations `Q^l (ug^alpha)^r = wz^alpha`, with coefficients derived from all instances.
+/// Returns the index of the first invalid proof, if the batch is rejected.
+/// All operations are `mod n`.
+///
+/// Note: Like all checks in `Z_n^*`, this does not detect errors of small order (e.g. `-1`),
+/// which can cancel out between instances.
+pub fn ni_poke2_batch_verify(instances: &[KnowledgeInstance], n: &BigUint) -> Result<(), usize> {
+    let mut transcript = Transcript::new();
+    for (u, w, (z, q, r)) in instances {
+        let r_abs = r.abs().to_biguint().unwrap();
+        transcript
+            .append(u)
+            .append(w)
+            .append(z)
+            .append(q)
+            .append(&r_abs)
+            .append(&BigUint::from(r.is_negative() as u8));
+    }
+    let cs = batch_coefficients(&transcript, instances.len());
+
+    let mut lhs = BTreeMap::new();
+    let mut rhs = BTreeMap::new();
+
+    let challenges = parallel::map(instances, |(u, w, (z, _, _))| {
+        // g <- H_G(u, w)
+        let g = poke2_group_element(u, w, n);
+
+        // l <- H_prime(u, w, z)
+        // alpha = H(u, w, z, l)
+        (g, poke2_challenge(u, w, z))
+    });
+
+    for (((u, w, (z, q, r)), c), (g, (l, alpha))) in instances.iter().zip(&cs).zip(challenges) {
+        // (Q^l u^r g^{alpha r})^c == (w z^alpha)^c
+        // Negative exponents are moved to the other side, to avoid inversions.
+        let cr = c * r.abs().to_biguint().unwrap();
+        let side = if r.is_negative() { &mut rhs } else { &mut lhs };
+        add_batch_term(side, u, cr.clone());
+        add_batch_term(side, &g, cr * &alpha);
+
+        add_batch_term(&mut lhs, q, c * &l);
+        add_batch_term(&mut rhs, w, c.clone());
+        add_batch_term(&mut rhs, z, c * &alpha);
+    }
+
+    if eval_batch_terms(&lhs, n) == eval_batch_terms(&rhs, n) {
+        return Ok(());
+    }
+
+    find_invalid(instances, |(u, w, pi)| ni_poke2_verify(u, w, pi, n))
+}
+
+/// Derives `count` coefficients for a random linear combination from the transcript.
+fn batch_coefficients(transcript: &Transcript, count: usize) -> Vec<BigUint> {
+    let seed = transcript.challenge();
+
+    (0..count)
+        .map(|i| {
+            Transcript::new()
+                .append(&seed)
+                .append(&BigUint::from(i as u64))
+                .challenge_bytes(BATCH_COEFFICIENT_BYTES)
+        })
+        .collect()
+}
+
+/// Adds `base^exp` to the product, merging repeated bases.
+fn add_batch_term(terms: &mut BTreeMap<BigUint, BigUint>, base: &BigUint, exp: BigUint) {
+    *terms.entry(base.clone()).or_default() += exp;
+}
+
+fn eval_batch_terms(terms: &BTreeMap<BigUint, BigUint>, n: &BigUint) -> BigUint {
+    let pairs = terms.iter().collect::<Vec<_>>();
+    multi_modpow(&pairs, n)
+}
+
+/// Falls back to verifying the instances one by one, to find the first invalid one.
+fn find_invalid<T, F>(instances: &[T], verify: F) -> Result<(), usize>
+where
+    T: Sync,
+    F: Fn(&T) -> bool + Sync + Send,
+{
+    match parallel::map(instances, verify)
+        .iter()
+        .position(|valid| !valid)
+    {
+        Some(i) => Err(i),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use num_bigint::{RandBigInt, RandPrime};
-    use num_traits::One;
     use rand::thread_rng;
 
+    #[test]
+    fn test_transcript_encoding() {
+        // the concatenations of the encodings are the same, 0x0102
+        let a = Transcript::new()
+            .append(&BigUint::from(1u32))
+            .append(&BigUint::from(2u32))
+            .challenge();
+        let b = Transcript::new()
+            .append(&BigUint::from(0x0102u32))
+            .challenge();
+        assert_ne!(a, b);
+    }
+
     #[test]
     fn test_ni_poe() {
         let mut rng = thread_rng();
@@ -186,4 +637,162 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_ni_poke() {
+        let mut rng = thread_rng();
+
+        for i in 1..4 {
+            for j in 1..4 {
+                let p = rng.gen_prime(i * 64);
+                let q = rng.gen_prime(i * 64);
+                let n = p * q;
+
+                let g = rng.gen_biguint(i * 128) % &n;
+                let x = rng.gen_biguint(j * 256);
+                let u = rng.gen_biguint(i * 128) % &n;
+                let w = u.modpow(&x, &n);
+
+                let pi = ni_poke_prove(&x, &g, &u, &w, &n);
+                assert!(ni_poke_verify(&g, &u, &w, &pi, &n));
+
+                let w_bad = (w * 2u32) % &n;
+                assert!(!ni_poke_verify(&g, &u, &w_bad, &pi, &n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ni_pokcr() {
+        let mut rng = thread_rng();
+
+        for i in 1..4 {
+            for m in 1..10 {
+                let p = rng.gen_prime(i * 64);
+                let q = rng.gen_prime(i * 64);
+                let n = p * q;
+
+                let xs = (0..m).map(|_| rng.gen_prime(128)).collect::<Vec<_>>();
+                let ws = (0..m)
+                    .map(|_| rng.gen_biguint(i * 128) % &n)
+                    .collect::<Vec<_>>();
+                let alphas = ws
+                    .iter()
+                    .zip(&xs)
+                    .map(|(w, x)| w.modpow(x, &n))
+                    .collect::<Vec<_>>();
+
+                let pi = ni_pokcr_prove(&ws, &n);
+                assert!(ni_pokcr_verify(&alphas, &xs, &pi, &n));
+
+                let pi_bad = (pi * 2u32) % &n;
+                assert!(!ni_pokcr_verify(&alphas, &xs, &pi_bad, &n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ni_poddh() {
+        let mut rng = thread_rng();
+
+        for i in 1..4 {
+            for j in 1..4 {
+                let p = rng.gen_prime(i * 64);
+                let q = rng.gen_prime(i * 64);
+                let n = p * q;
+
+                let g = rng.gen_biguint(i * 128) % &n;
+                let a = rng.gen_biguint(j * 128);
+                let b = rng.gen_biguint(j * 128);
+
+                let y1 = g.modpow(&a, &n);
+                let y2 = g.modpow(&b, &n);
+                let y3 = y2.modpow(&a, &n);
+
+                let pi = ni_poddh_prove(&a, &b, &g, &y1, &y2, &y3, &n);
+                assert!(ni_poddh_verify(&g, &y1, &y2, &y3, &pi, &n));
+
+                // not a DDH tuple
+                let y3_bad = y2.modpow(&(a + 1u32), &n);
+                assert!(!ni_poddh_verify(&g, &y1, &y2, &y3_bad, &pi, &n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_ni_poe_batch() {
+        let mut rng = thread_rng();
+
+        let p = rng.gen_prime(128);
+        let q = rng.gen_prime(128);
+        let n = p * q;
+
+        let mut values = Vec::new();
+        for j in 1..10 {
+            let mut x = BigUint::one();
+            for _ in 0..j {
+                x *= rng.gen_prime(128);
+            }
+            let u = rng.gen_biguint(256);
+            let w = u.modpow(&x, &n);
+            let q = ni_poe_prove(&x, &u, &w, &n);
+
+            values.push((x, u, w, q));
+        }
+
+        let instances = values
+            .iter()
+            .map(|(x, u, w, q)| (x, u, w, q))
+            .collect::<Vec<_>>();
+        assert_eq!(ni_poe_batch_verify(&instances, &n), Ok(()));
+        assert_eq!(ni_poe_batch_verify(&[], &n), Ok(()));
+
+        // invalid proof
+        let bad = (values[4].3.clone() * 2u32) % &n;
+        let mut instances = instances;
+        instances[4].3 = &bad;
+        assert_eq!(ni_poe_batch_verify(&instances, &n), Err(4));
+
+        // unreduced w, rejected like by ni_poe_verify
+        let (x, u, w, q) = &values[2];
+        let unreduced = w + &n;
+        assert!(!ni_poe_verify(x, u, &unreduced, q, &n));
+        instances[4].3 = &values[4].3;
+        instances[2].2 = &unreduced;
+        assert_eq!(ni_poe_batch_verify(&instances, &n), Err(2));
+    }
+
+    #[test]
+    fn test_ni_poke2_batch() {
+        let mut rng = thread_rng();
+
+        let p = rng.gen_prime(128);
+        let q = rng.gen_prime(128);
+        let n = p * q;
+
+        let mut values = Vec::new();
+        for j in 1..10 {
+            let mut x: BigInt = rng.gen_prime(j * 64).into();
+            if j % 2 == 0 {
+                x = -x;
+            }
+            let u = rng.gen_prime(128);
+            let w = modpow_uint_int(&u, &x, &n).unwrap();
+            let pi = ni_poke2_prove(x, &u, &w, &n);
+
+            values.push((u, w, pi));
+        }
+
+        let instances = values
+            .iter()
+            .map(|(u, w, pi)| (u, w, pi))
+            .collect::<Vec<_>>();
+        assert_eq!(ni_poke2_batch_verify(&instances, &n), Ok(()));
+
+        // invalid statement
+        let bad = (values[7].1.clone() * 2u32) % &n;
+        let mut instances = instances;
+        instances[7].1 = &bad;
+        assert_eq!(ni_poke2_batch_verify(&instances, &n), Err(7));
+    }
 }
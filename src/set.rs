@@ -0,0 +1,328 @@
+//! Representations of the product of the accumulated elements.
+//!
+//! - [BigUint]: a single integer, which is simple, but every change rewrites the whole product.
+//! - [ChunkedSet]: the products of fixed size chunks of elements, such that adding and deleting
+//!   only touches a single chunk, and the path above it in a product tree over the chunks.
+//!
+//! The representation only changes the cost of maintaining the product. Deleting without a
+//! witness and creating non-membership witnesses still exponentiate with the whole product, and
+//! stay linear in the size of the set for both.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::math::product;
+
+use crate::traits::AccumulatedSet;
+
+/// Default number of elements per chunk in a [ChunkedSet].
+pub const DEFAULT_CHUNK_SIZE: usize = 256;
+
+impl AccumulatedSet for BigUint {
+    fn empty() -> Self {
+        BigUint::one()
+    }
+
+    fn insert(&mut self, x: &BigUint) {
+        *self *= x;
+    }
+
+    fn insert_all(&mut self, xs: &[BigUint]) {
+        *self *= product(xs);
+    }
+
+    fn remove(&mut self, x: &BigUint) -> Option<()> {
+        let (q, r) = self.div_rem(x);
+        if !r.is_zero() {
+            return None;
+        }
+
+        *self = q;
+        Some(())
+    }
+
+    fn contains(&self, x: &BigUint) -> bool {
+        (self % x).is_zero()
+    }
+
+    fn product(&self) -> Cow<'_, BigUint> {
+        Cow::Borrowed(self)
+    }
+
+    fn quotient(&self, x: &BigUint) -> BigUint {
+        let (q, r) = self.div_rem(x);
+        debug_assert!(r.is_zero(), "x was not a valid member of set");
+
+        q
+    }
+
+    fn residue(&self, x: &BigUint) -> BigUint {
+        self % x
+    }
+
+    fn bits(&self) -> usize {
+        BigUint::bits(self)
+    }
+}
+
+/// The accumulated product, split into chunks of at most `chunk_size` elements.
+///
+/// Adding or deleting an element only multiplies or divides its chunk, and `mod x` is reduced
+/// per chunk. The products of the chunks are kept in a balanced product tree. Deleting updates
+/// the path above the chunk, while the nodes above newly added elements are only recomputed
+/// when the product is needed, or by the next deletion.
+///
+/// Members are looked up directly, while products of several members are checked by reducing
+/// every chunk, like [AccumulatedSet::residue].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedSet {
+    chunk_size: usize,
+    /// The levels of the product tree, starting with the product of the elements in each chunk,
+    /// followed by the products of pairs of the previous level, up to the full product.
+    tree: Vec<Vec<BigUint>>,
+    /// The first chunk whose ancestors in `tree` are outdated, or missing.
+    stale: usize,
+    /// The number of elements in each chunk.
+    counts: Vec<usize>,
+    /// The chunk of each element.
+    index: HashMap<BigUint, usize>,
+}
+
+impl ChunkedSet {
+    pub fn new(chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "invalid chunk size");
+
+        ChunkedSet {
+            chunk_size,
+            tree: vec![Vec::new()],
+            stale: 0,
+            counts: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns the products of the elements in each chunk.
+    fn chunks(&self) -> &[BigUint] {
+        &self.tree[0]
+    }
+
+    /// Returns the number of nodes on each level of the tree, starting with the chunks.
+    fn level_lens(&self) -> Vec<usize> {
+        let mut lens = vec![self.chunks().len()];
+        let mut len = lens[0];
+        while len > 1 {
+            len = Integer::div_ceil(&len, &2);
+            lens.push(len);
+        }
+
+        lens
+    }
+
+    /// Returns the product of the chunks below node `j` on `level`, recomputing outdated nodes.
+    fn node(&self, lens: &[usize], level: usize, j: usize) -> Cow<'_, BigUint> {
+        let end = std::cmp::min((j + 1) << level, lens[0]);
+        if level == 0 || end <= self.stale {
+            return Cow::Borrowed(&self.tree[level][j]);
+        }
+
+        let left = self.node(lens, level - 1, 2 * j);
+        if 2 * j + 1 < lens[level - 1] {
+            Cow::Owned(left.as_ref() * self.node(lens, level - 1, 2 * j + 1).as_ref())
+        } else {
+            left
+        }
+    }
+
+    /// Recomputes the outdated nodes, bottom up.
+    fn refresh(&mut self) {
+        let lens = self.level_lens();
+        for level in 1..lens.len() {
+            if self.tree.len() == level {
+                self.tree.push(Vec::new());
+            }
+
+            let (lower, upper) = self.tree.split_at_mut(level);
+            let (children, nodes) = (&lower[level - 1], &mut upper[0]);
+            for j in self.stale >> level..lens[level] {
+                let node = match children.get(2 * j + 1) {
+                    Some(right) => &children[2 * j] * right,
+                    None => children[2 * j].clone(),
+                };
+                if j < nodes.len() {
+                    nodes[j] = node;
+                } else {
+                    nodes.push(node);
+                }
+            }
+        }
+
+        self.stale = lens[0];
+    }
+}
+
+impl AccumulatedSet for ChunkedSet {
+    fn empty() -> Self {
+        ChunkedSet::new(DEFAULT_CHUNK_SIZE)
+    }
+
+    fn insert(&mut self, x: &BigUint) {
+        debug_assert!(!self.contains(x), "{} was already added", x);
+
+        let full = match self.counts.last() {
+            Some(count) => *count >= self.chunk_size,
+            None => true,
+        };
+        if full {
+            self.tree[0].push(BigUint::one());
+            self.counts.push(0);
+        }
+
+        let i = self.counts.len() - 1;
+        self.tree[0][i] *= x;
+        self.counts[i] += 1;
+        self.index.insert(x.clone(), i);
+        self.stale = std::cmp::min(self.stale, i);
+    }
+
+    fn remove(&mut self, x: &BigUint) -> Option<()> {
+        let mut i = self.index.remove(x)?;
+        self.tree[0][i] /= x;
+        self.counts[i] -= 1;
+
+        // only the path above chunk i changes
+        self.refresh();
+        for level in 1..self.tree.len() {
+            let (lower, upper) = self.tree.split_at_mut(level);
+            let children = &lower[level - 1];
+            i /= 2;
+            upper[0][i] = match children.get(2 * i + 1) {
+                Some(right) => &children[2 * i] * right,
+                None => children[2 * i].clone(),
+            };
+        }
+
+        Some(())
+    }
+
+    fn contains(&self, x: &BigUint) -> bool {
+        // single members are looked up, products of several members are reduced per chunk
+        self.index.contains_key(x) || self.residue(x).is_zero()
+    }
+
+    fn product(&self) -> Cow<'_, BigUint> {
+        if self.chunks().is_empty() {
+            return Cow::Owned(BigUint::one());
+        }
+
+        let lens = self.level_lens();
+        self.node(&lens, lens.len() - 1, 0)
+    }
+
+    fn quotient(&self, x: &BigUint) -> BigUint {
+        let mut i = match self.index.get(x) {
+            Some(i) => *i,
+            // a product of several elements
+            None => {
+                let (q, r) = self.product().div_rem(x);
+                debug_assert!(r.is_zero(), "x was not a valid member of set");
+                return q;
+            }
+        };
+        let lens = self.level_lens();
+
+        // multiply the siblings along the path to the root
+        let mut res = &self.chunks()[i] / x;
+        for level in 0..lens.len() - 1 {
+            if (i ^ 1) < lens[level] {
+                res *= self.node(&lens, level, i ^ 1).as_ref();
+            }
+            i /= 2;
+        }
+
+        res
+    }
+
+    fn residue(&self, x: &BigUint) -> BigUint {
+        self.chunks()
+            .iter()
+            .fold(BigUint::one() % x, |acc, chunk| (acc * (chunk % x)) % x)
+    }
+
+    fn bits(&self) -> usize {
+        self.chunks().iter().map(|chunk| chunk.bits()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::RandPrime;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_chunked_set() {
+        let mut rng = thread_rng();
+
+        let mut set = ChunkedSet::new(4);
+        let mut expected = BigUint::empty();
+        let mut members = Vec::new();
+
+        for _ in 0..100 {
+            if members.is_empty() || rng.gen_bool(0.7) {
+                let x = rng.gen_prime(64);
+                set.insert(&x);
+                expected.insert(&x);
+                members.push(x);
+            } else {
+                let x = members.swap_remove(rng.gen_range(0..members.len()));
+                assert_eq!(set.remove(&x), Some(()));
+                assert_eq!(expected.remove(&x), Some(()));
+                assert_eq!(set.remove(&x), None);
+                assert_eq!(expected.remove(&x), None);
+                assert_eq!(set.product(), expected.product());
+            }
+
+            assert_eq!(set.len(), members.len());
+            if rng.gen_bool(0.5) {
+                assert_eq!(set.product(), expected.product());
+            }
+            assert!(set.bits() >= expected.bits());
+
+            let y = rng.gen_prime(64);
+            assert_eq!(set.residue(&y), expected.residue(&y));
+
+            // the newest members lie below outdated nodes
+            for x in members.first().into_iter().chain(members.last()) {
+                assert!(set.contains(x) && expected.contains(x));
+                assert_eq!(set.quotient(x), expected.quotient(x));
+                assert!(set.residue(x).is_zero());
+            }
+            assert!(!set.contains(&y) && !expected.contains(&y));
+
+            // products of several members
+            if members.len() > 2 {
+                let x = &members[0] * &members[members.len() / 2];
+                assert!(set.contains(&x) && expected.contains(&x));
+                assert_eq!(set.quotient(&x), expected.quotient(&x));
+
+                let x = &members[0] * &y;
+                assert!(!set.contains(&x) && !expected.contains(&x));
+            }
+        }
+    }
+}
@@ -1,8 +1,45 @@
+use std::borrow::Cow;
+use std::fmt::Debug;
+
 use failure::Error;
 use num_bigint::{BigInt, BigUint};
+use num_traits::One;
 use rand::CryptoRng;
 use rand::Rng;
 
+use crate::error::VectorCommitmentError;
+use crate::math::{backend, multi_modpow, MontgomeryContext};
+use crate::proofs::Transcript;
+
+/// The proof of [BatchedAccumulator::non_mem_wit_create_star].
+pub type NonMemStarProof = (BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint);
+
+/// `Send` with the `parallel` feature enabled, and implemented by all types otherwise.
+#[cfg(feature = "parallel")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "parallel")]
+impl<T: Send> MaybeSend for T {}
+
+/// `Send` with the `parallel` feature enabled, and implemented by all types otherwise.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "parallel"))]
+impl<T> MaybeSend for T {}
+
+/// `Sync` with the `parallel` feature enabled, and implemented by all types otherwise, such that
+/// only the `parallel` feature requires accumulators to be shared between threads.
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "parallel")]
+impl<T: Sync> MaybeSync for T {}
+
+/// `Sync` with the `parallel` feature enabled, and implemented by all types otherwise, such that
+/// only the `parallel` feature requires accumulators to be shared between threads.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "parallel"))]
+impl<T> MaybeSync for T {}
+
 pub trait StaticAccumulator {
     /// Setup generates a group of unknown order and initializes the group
     /// with a generator of that group.
@@ -21,18 +58,31 @@ pub trait StaticAccumulator {
     /// Verify a membership proof.
     fn ver_mem(&self, w: &BigUint, x: &BigUint) -> bool;
 
+    /// Returns `true` if `x` divides the product of the accumulated elements, i.e. is a member
+    /// or a product of distinct members.
+    fn contains(&self, x: &BigUint) -> bool;
+
     fn state(&self) -> &BigUint;
+
+    /// Returns the modulus `n` of the group.
+    fn modulus(&self) -> &BigUint;
+
+    /// Returns the generator `g`, the state of the empty accumulator.
+    fn generator(&self) -> &BigUint;
 }
 
 pub trait DynamicAccumulator: StaticAccumulator {
     /// Delete a value from the accumulator.
+    ///
+    /// Without a witness for `x` the new state is recomputed from the remaining elements,
+    /// [BatchedAccumulator::del_w_mem] and [BatchedAccumulator::batch_del] avoid this.
     fn del(&mut self, x: &BigUint) -> Option<()>;
 }
 
 pub trait UniversalAccumulator: DynamicAccumulator {
     /// Create a non-membership proof.
-    /// Returns `None`, iff `x` is a member.
-    fn non_mem_wit_create(&self, x: &BigUint) -> (BigUint, BigInt);
+    /// Returns `None`, iff `x` shares a factor with the accumulated elements, e.g. is a member.
+    fn non_mem_wit_create(&self, x: &BigUint) -> Option<(BigUint, BigInt)>;
 
     /// Verify a non-membership proof.
     fn ver_non_mem(&self, w: &(BigUint, BigInt), x: &BigUint) -> bool;
@@ -45,6 +95,8 @@ pub trait BatchedAccumulator: StaticAccumulator {
 
     /// Batch delete.
     /// Given a list of witnesses and members, deletes all of them.
+    /// Returns `None`, leaving the accumulator unchanged, if an element is not a member,
+    /// a witness is invalid, or the elements are not pairwise coprime.
     fn batch_del(&mut self, pairs: &[(BigUint, BigUint)]) -> Option<BigUint>;
 
     /// Delete with member witness.
@@ -56,6 +108,10 @@ pub trait BatchedAccumulator: StaticAccumulator {
     /// Needs to be passed in, as we don't hold onto the whole set in the accumulator currently.
     fn create_all_mem_wit(&self, s: &[BigUint]) -> Vec<BigUint>;
 
+    /// Create membership witnesses for the members `xs`, which unlike for [create_all_mem_wit]
+    /// need not be all elements. Returns `None` if an element is not a member, or given twice.
+    fn mem_wit_create_batch(&self, xs: &[BigUint]) -> Option<Vec<BigUint>>;
+
     /// Verify Batch Add.
     /// Given the proof `w` from [batch_add] and the list of members `xs`,
     /// and the previous state of the accumulator `a_t` this verifies if the `add` was done correctly.
@@ -102,17 +158,47 @@ pub trait BatchedAccumulator: StaticAccumulator {
     fn ver_mem_x(&self, other: &BigUint, pi: &BigUint, x: &BigUint, y: &BigUint) -> bool;
 
     /// Efficient non membership proof.
-    fn non_mem_wit_create_star(
-        &self,
-        x: &BigUint,
-    ) -> (BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint);
+    /// Returns `None` like [UniversalAccumulator::non_mem_wit_create].
+    fn non_mem_wit_create_star(&self, x: &BigUint) -> Option<NonMemStarProof>;
 
     /// Verify non membership proof.
-    fn ver_non_mem_star(
-        &self,
-        x: &BigUint,
-        pi: &(BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint),
-    ) -> bool;
+    fn ver_non_mem_star(&self, x: &BigUint, pi: &NonMemStarProof) -> bool;
+}
+
+/// The product of all elements in an accumulator, see [crate::set] for the representations.
+/// All elements are assumed to be pairwise coprime, and never added twice.
+pub trait AccumulatedSet: Clone + Debug + Send + Sync {
+    /// Returns the empty product.
+    fn empty() -> Self;
+
+    /// Multiplies `x` into the product.
+    fn insert(&mut self, x: &BigUint);
+
+    /// Multiplies all `xs` into the product.
+    fn insert_all(&mut self, xs: &[BigUint]) {
+        for x in xs {
+            self.insert(x);
+        }
+    }
+
+    /// Divides `x` out of the product.
+    /// Returns `None` if `x` is not a factor.
+    fn remove(&mut self, x: &BigUint) -> Option<()>;
+
+    /// Returns `true` if `x` is a factor, i.e. a member or a product of distinct members.
+    fn contains(&self, x: &BigUint) -> bool;
+
+    /// Returns the product.
+    fn product(&self) -> Cow<'_, BigUint>;
+
+    /// Returns the product divided by `x`, which must be a factor.
+    fn quotient(&self, x: &BigUint) -> BigUint;
+
+    /// Returns the product `mod x`.
+    fn residue(&self, x: &BigUint) -> BigUint;
+
+    /// Returns an upper bound on the number of bits of the product.
+    fn bits(&self) -> usize;
 }
 
 pub trait StaticVectorCommitment {
@@ -120,27 +206,49 @@ pub trait StaticVectorCommitment {
     type Commitment;
     type BatchCommitment;
 
+    /// Creates an empty vector commitment, for up to `n` entries.
     fn setup<T, R>(rng: &mut R, int_size_bits: usize, n: usize) -> Self
     where
         T: PrimeGroup,
         R: CryptoRng + Rng;
 
-    fn commit(&mut self, m: &[Self::Domain]);
+    /// Appends `m` to the committed vector.
+    fn commit(&mut self, m: &[Self::Domain]) -> Result<(), VectorCommitmentError>;
 
-    fn open(&self, b: &Self::Domain, i: usize) -> Self::Commitment;
+    fn open(&self, b: &Self::Domain, i: usize) -> Result<Self::Commitment, VectorCommitmentError>;
 
+    /// Verifies an opening, rejecting all indices `>= len()`.
     fn verify(&self, b: &Self::Domain, i: usize, pi: &Self::Commitment) -> bool;
 
-    fn batch_open(&self, b: &[Self::Domain], i: &[usize]) -> Self::BatchCommitment;
+    fn batch_open(
+        &self,
+        b: &[Self::Domain],
+        i: &[usize],
+    ) -> Result<Self::BatchCommitment, VectorCommitmentError>;
 
     fn batch_verify(&self, b: &[Self::Domain], i: &[usize], pi: &Self::BatchCommitment) -> bool;
 
     fn state(&self) -> &BigUint;
+
+    /// Returns the number of committed entries, which is bound into the state.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum number of entries.
+    fn capacity(&self) -> usize;
 }
 
 pub trait DynamicVectorCommitment: StaticVectorCommitment {
     /// Changes the value at position `i`, from `b_prime`  to `b`.
-    fn update(&mut self, b: &Self::Domain, b_prime: &Self::Domain, i: usize);
+    fn update(
+        &mut self,
+        b: &Self::Domain,
+        b_prime: &Self::Domain,
+        i: usize,
+    ) -> Result<(), VectorCommitmentError>;
 }
 
 /// This trait abstracts the Group of unknown order that is used to sample our primes
@@ -153,3 +261,100 @@ pub trait PrimeGroup {
         int_size_bits: usize,
     ) -> Result<(BigUint, BigUint), Error>;
 }
+
+/// A group of unknown order, given by its public parameters, in which the verifiable delay
+/// functions are evaluated: either `Z_n^*` for an RSA modulus `n`, or the class group of a
+/// [crate::group::Discriminant].
+pub trait UnknownOrderGroup {
+    type Elem: Clone + PartialEq + Debug;
+
+    /// Returns the neutral element.
+    fn identity(&self) -> Self::Elem;
+
+    /// Returns `a b`.
+    fn op(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
+
+    /// Returns `a^2`.
+    fn square(&self, a: &Self::Elem) -> Self::Elem {
+        self.op(a, a)
+    }
+
+    /// Returns `a^e`.
+    fn pow(&self, a: &Self::Elem, e: &BigUint) -> Self::Elem {
+        let mut res = self.identity();
+        for byte in e.to_bytes_be() {
+            for i in (0..8).rev() {
+                res = self.square(&res);
+                if (byte >> i) & 1 == 1 {
+                    res = self.op(&res, a);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Returns `a^(2^t)`, by `t` sequential squarings.
+    fn repeated_square(&self, a: &Self::Elem, t: u64) -> Self::Elem {
+        let mut res = a.clone();
+        for _ in 0..t {
+            res = self.square(&res);
+        }
+
+        res
+    }
+
+    /// Returns `prod a_i^e_i`.
+    fn multi_pow(&self, pairs: &[(&Self::Elem, &BigUint)]) -> Self::Elem {
+        pairs.iter().fold(self.identity(), |acc, (a, e)| {
+            self.op(&acc, &self.pow(a, e))
+        })
+    }
+
+    /// Appends the canonical encoding of `a` to the transcript.
+    fn transcribe(&self, a: &Self::Elem, transcript: &mut Transcript);
+
+    /// Returns the representative of `a` in groups, whose elements are only considered up to
+    /// their sign, and `a` itself otherwise.
+    fn normalize(&self, a: &Self::Elem) -> Self::Elem {
+        a.clone()
+    }
+}
+
+/// `Z_n^*`, given by the modulus `n`.
+impl UnknownOrderGroup for BigUint {
+    type Elem = BigUint;
+
+    fn identity(&self) -> BigUint {
+        BigUint::one() % self
+    }
+
+    fn op(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % self
+    }
+
+    fn pow(&self, a: &BigUint, e: &BigUint) -> BigUint {
+        backend::modpow(a, e, self)
+    }
+
+    fn repeated_square(&self, a: &BigUint, t: u64) -> BigUint {
+        let ctx = MontgomeryContext::new(self);
+        ctx.from_repr(&ctx.repeated_square(&ctx.to_repr(a), t))
+    }
+
+    fn multi_pow(&self, pairs: &[(&BigUint, &BigUint)]) -> BigUint {
+        multi_modpow(pairs, self)
+    }
+
+    fn transcribe(&self, a: &BigUint, transcript: &mut Transcript) {
+        transcript.append(&(a % self));
+    }
+
+    /// Returns `min(a, n - a)`, the representative of `±a` in the signed quadratic residues
+    /// `QR_n^+`, in which `-1` can not be used to forge proofs.
+    fn normalize(&self, a: &BigUint) -> BigUint {
+        let a = a % self;
+        let neg = self - &a;
+        std::cmp::min(a, neg)
+    }
+}
@@ -1,5 +1,13 @@
+use std::collections::HashSet;
+
+use crate::accumulator::{non_mem_wit_star, ver_non_mem_star};
+use crate::error::VectorCommitmentError;
 use crate::hash::hash_prime;
+use crate::math::{backend, mem_wit_update, non_mem_wit_update, product, root_factor};
+use crate::parallel;
+use crate::proofs;
 use crate::traits::*;
+use crate::vc::{index_prime, index_primes};
 use blake2::Blake2b512;
 use byteorder::{BigEndian, ByteOrder};
 use num_bigint::{BigInt, BigUint};
@@ -13,6 +21,8 @@ pub struct BinaryVectorCommitment<A: UniversalAccumulator + BatchedAccumulator>
     lambda: usize,
     n: usize,
     acc: A,
+    /// The state without the length prime, which is the witness of the length.
+    u: BigUint,
     pos: usize,
 }
 
@@ -23,6 +33,55 @@ pub enum Commitment {
     NonMem((BigUint, BigInt)),
 }
 
+/// An update of position `i` from `b_prime` to `b`, see [DynamicVectorCommitment::update], together
+/// with the state before it. This is all holders of openings need to update them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorUpdate<D> {
+    pub old_state: BigUint,
+    pub b: D,
+    pub b_prime: D,
+    pub i: usize,
+}
+
+/// The witnesses behind a [BatchCommitment], which unlike the proofs can be kept up to date.
+/// `w` is the membership witness for the product of the primes of all positions set to `1`,
+/// and `(d, b)` the non-membership witness for those set to `0`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchWitness {
+    pub w: BigUint,
+    pub d: BigUint,
+    pub b: BigInt,
+}
+
+/// Proof that a vector was extended by some entries, `(u, Q)`.
+/// `u` is the old state without its length prime, and `Q` a NI-PoE that `u` raised to the primes
+/// of the appended entries and the new length is the new state.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendProof {
+    pub u: BigUint,
+    pub q: BigUint,
+}
+
+/// Proof of a batch update, `(u, Q_del, Q_add, non_mem)`.
+/// `u` is the old state without the primes of the cleared positions, `Q_del` a NI-PoE that `u`
+/// raised to them is the old state, and `Q_add` one that `u` raised to the primes of the set
+/// positions is the new state. `non_mem` proves that the set positions were unset in `u`, see
+/// [BatchedAccumulator::non_mem_wit_create_star].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateProof {
+    pub u: BigUint,
+    pub q_del: BigUint,
+    pub q_add: BigUint,
+    pub non_mem: (BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint),
+}
+
+/// Opening of several positions. Together with the proofs, it holds the exponent `b` of the
+/// non-membership witness, such that it can be kept up to date, see
+/// [BinaryVectorCommitment::update_batch_opening].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BatchCommitment(
@@ -30,9 +89,330 @@ pub struct BatchCommitment(
     (BigUint, BigUint),
     // non membership proof
     (BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint),
+    // exponent of the non membership witness
+    BigInt,
 );
 
-impl<A: UniversalAccumulator + BatchedAccumulator> StaticVectorCommitment
+impl BatchCommitment {
+    /// Returns the witnesses the proofs were created from.
+    pub(super) fn witness(&self) -> BatchWitness {
+        BatchWitness {
+            w: (self.0).0.clone(),
+            d: (self.1).0.clone(),
+            b: self.2.clone(),
+        }
+    }
+}
+
+impl<A: UniversalAccumulator + BatchedAccumulator + MaybeSync> BinaryVectorCommitment<A> {
+    /// Returns the modulus of the group.
+    pub fn modulus(&self) -> &BigUint {
+        self.acc.modulus()
+    }
+
+    /// Proves the length of the vector, returning a membership witness for its length prime.
+    pub fn prove_len(&self) -> BigUint {
+        self.u.clone()
+    }
+
+    /// Verifies that the committed vector has length `len`.
+    pub fn verify_len(&self, len: usize, pi: &BigUint) -> bool {
+        self.acc.ver_mem(pi, &map_len_to_p(len))
+    }
+
+    /// Appends `m` to the committed vector, and proves that the new state extends the old one
+    /// by exactly `m`.
+    pub fn append(&mut self, m: &[bool]) -> Result<AppendProof, VectorCommitmentError> {
+        if m.len() > self.n - self.pos {
+            return Err(VectorCommitmentError::CapacityExceeded {
+                len: self.pos,
+                additional: m.len(),
+                capacity: self.n,
+            });
+        }
+
+        // the old state is u^{p_old}, so u is the witness of the old length prime
+        let u = self.u.clone();
+        self.acc
+            .del_w_mem(&u, &map_len_to_p(self.pos))
+            .expect("invalid state");
+
+        let primes = append_primes(self.pos, m);
+        self.pos += m.len();
+        let q = self.acc.batch_add(&primes);
+
+        let (_, set) = primes.split_last().expect("length prime");
+        self.u = backend::modpow(&u, &product(set), self.acc.modulus());
+
+        Ok(AppendProof { u, q })
+    }
+
+    /// Verifies that the vector with state `new_state` extends the vector with state `old_state`
+    /// and length `old_len` by exactly `m`, in the group with modulus `modulus`.
+    pub fn verify_append(
+        modulus: &BigUint,
+        old_state: &BigUint,
+        old_len: usize,
+        new_state: &BigUint,
+        m: &[bool],
+        pi: &AppendProof,
+    ) -> bool {
+        // u^{p_old} = old state
+        let p_old = map_len_to_p(old_len);
+        if &backend::modpow(&pi.u, &p_old, modulus) != old_state {
+            return false;
+        }
+
+        // u^{\prod p_i p_new} = new state
+        let x = product(&append_primes(old_len, m));
+        proofs::ni_poe_verify(&x, &pi.u, new_state, &pi.q, modulus)
+    }
+
+    /// Changes the values of many positions at once, given as `(i, b_prime, b)` for a change of
+    /// position `i` from `b_prime` to `b`. Returns an error, leaving the vector unchanged, if a
+    /// position is given twice, or any position does not hold its `b_prime`.
+    ///
+    /// The cleared positions are deleted with a single [BatchedAccumulator::batch_del], the set
+    /// ones added with a single [BatchedAccumulator::batch_add].
+    pub fn batch_update(
+        &mut self,
+        updates: &[(usize, bool, bool)],
+    ) -> Result<UpdateProof, VectorCommitmentError> {
+        self.check_updates(updates)?;
+
+        let (added, deleted) = batch_update_primes(updates);
+        let q_del = if deleted.is_empty() {
+            // u^1 = u, so Q = 1
+            BigUint::one()
+        } else {
+            self.delete_primes(&deleted)
+        };
+        let u = self.acc.state().clone();
+
+        let p_added = product(&added);
+        let non_mem = if p_added.is_one() {
+            empty_non_mem_proof()
+        } else {
+            self.acc
+                .non_mem_wit_create_star(&p_added)
+                .expect("checked that the added positions are unset")
+        };
+        let q_add = self.add_primes(&added);
+
+        Ok(UpdateProof {
+            u,
+            q_del,
+            q_add,
+            non_mem,
+        })
+    }
+
+    /// Verifies that the committed vector is the vector with state `old_state`, changed by
+    /// `updates`, see [BinaryVectorCommitment::batch_update].
+    pub fn verify_batch_update(
+        &self,
+        old_state: &BigUint,
+        updates: &[(usize, bool, bool)],
+        pi: &UpdateProof,
+    ) -> bool {
+        let mut seen = HashSet::new();
+        if updates
+            .iter()
+            .any(|(i, _, _)| *i >= self.pos || !seen.insert(*i))
+        {
+            return false;
+        }
+
+        let (added, deleted) = batch_update_primes(updates);
+        let p_added = product(&added);
+        let n = self.acc.modulus();
+
+        // the cleared positions were set in the old state, and the set ones unset in u
+        proofs::ni_poe_verify(&product(&deleted), &pi.u, old_state, &pi.q_del, n)
+            && (p_added.is_one()
+                || ver_non_mem_star(&p_added, &pi.non_mem, &pi.u, self.acc.generator(), n))
+            && self.acc.ver_batch_add(&pi.q_add, &pi.u, &added)
+    }
+
+    /// Updates the opening `pi` of position `j`, created before the update `u`, to the state
+    /// `new_state` after it, in the group with modulus `modulus`. Returns `None` if the update
+    /// changed position `j` itself.
+    pub fn update_opening(
+        modulus: &BigUint,
+        new_state: &BigUint,
+        pi: &Commitment,
+        j: usize,
+        u: &VectorUpdate<bool>,
+    ) -> Option<Commitment> {
+        if j == u.i && u.b != u.b_prime {
+            return None;
+        }
+
+        let p_j = index_prime(j);
+        let (added, deleted) = update_primes(u);
+
+        match pi {
+            Commitment::Mem(w) => {
+                mem_wit_update(w, &p_j, &added, &deleted, new_state, modulus).map(Commitment::Mem)
+            }
+            Commitment::NonMem(w) => {
+                non_mem_wit_update(w, &p_j, &added, &deleted, &u.old_state, new_state, modulus)
+                    .map(Commitment::NonMem)
+            }
+        }
+    }
+
+    /// Updates the batch opening `pi` of the bits `b` at positions `i`, created before the update
+    /// `u`, like [BinaryVectorCommitment::update_opening]. Returns `None` if the update changed
+    /// one of the positions, or `b` and `i` differ in length.
+    pub fn update_batch_opening(
+        modulus: &BigUint,
+        new_state: &BigUint,
+        pi: &BatchCommitment,
+        b: &[bool],
+        i: &[usize],
+        u: &VectorUpdate<bool>,
+    ) -> Option<BatchCommitment> {
+        let wit = Self::update_witness(modulus, new_state, &pi.witness(), b, i, u)?;
+
+        Some(prove_witness_primes(
+            modulus,
+            new_state,
+            &wit,
+            partition_primes(b, &index_primes(i)),
+        ))
+    }
+
+    /// Opens the bits `b` at positions `i`, like [StaticVectorCommitment::batch_open], but returns
+    /// the witnesses, see [BinaryVectorCommitment::prove_witness].
+    pub fn open_witness(
+        &self,
+        b: &[bool],
+        i: &[usize],
+    ) -> Result<BatchWitness, VectorCommitmentError> {
+        check_lengths(b, i)?;
+        for i_j in i {
+            self.check_index(*i_j)?;
+        }
+
+        let (p_ones, p_zeros) = partition_primes(b, &index_primes(i));
+        if !self.acc.contains(&p_ones) {
+            return Err(VectorCommitmentError::ValueMismatch);
+        }
+        let (w, non_mem) = parallel::join(
+            || self.acc.mem_wit_create(&p_ones),
+            || self.acc.non_mem_wit_create(&p_zeros),
+        );
+        let (d, b) = non_mem.ok_or(VectorCommitmentError::ValueMismatch)?;
+
+        Ok(BatchWitness { w, d, b })
+    }
+
+    /// Updates the witness `wit` for the bits `b` at positions `i`, created before the update
+    /// `u`, like [BinaryVectorCommitment::update_opening]. Returns `None` if the update changed
+    /// one of the positions, or `b` and `i` differ in length.
+    pub fn update_witness(
+        modulus: &BigUint,
+        new_state: &BigUint,
+        wit: &BatchWitness,
+        b: &[bool],
+        i: &[usize],
+        u: &VectorUpdate<bool>,
+    ) -> Option<BatchWitness> {
+        check_lengths(b, i).ok()?;
+        if i.contains(&u.i) && u.b != u.b_prime {
+            return None;
+        }
+
+        let (added, deleted) = update_primes(u);
+        update_witness_primes(
+            modulus,
+            new_state,
+            wit,
+            partition_primes(b, &index_primes(i)),
+            &u.old_state,
+            (&added, &deleted),
+        )
+    }
+
+    /// Creates the batch opening for the bits `b` at positions `i`, from their witness.
+    pub fn prove_witness(
+        &self,
+        wit: &BatchWitness,
+        b: &[bool],
+        i: &[usize],
+    ) -> Result<BatchCommitment, VectorCommitmentError> {
+        check_lengths(b, i)?;
+
+        Ok(prove_witness_primes(
+            self.acc.modulus(),
+            self.acc.state(),
+            wit,
+            partition_primes(b, &index_primes(i)),
+        ))
+    }
+
+    /// Adds the primes `added`, keeping `u` up to date, and returns the proof of
+    /// [BatchedAccumulator::batch_add].
+    fn add_primes(&mut self, added: &[BigUint]) -> BigUint {
+        let q = self.acc.batch_add(added);
+        self.u = backend::modpow(&self.u, &product(added), self.acc.modulus());
+
+        q
+    }
+
+    /// Deletes the members `deleted`, keeping `u` up to date, and returns the proof of
+    /// [BatchedAccumulator::batch_del].
+    ///
+    /// The new `u` is the witness of all deleted primes and the length prime, from which the
+    /// witnesses of the deleted primes follow, so only one exponentiation covers the whole set.
+    fn delete_primes(&mut self, deleted: &[BigUint]) -> BigUint {
+        let n = self.acc.modulus();
+        let p_len = map_len_to_p(self.pos);
+
+        let u = self.acc.mem_wit_create(&(product(deleted) * &p_len));
+        // the witnesses of all x_i are the (D / x_i)-th powers of the new state u^{p_len}
+        let ws = root_factor(&backend::modpow(&u, &p_len, n), deleted, n);
+        let pairs = deleted.iter().cloned().zip(ws).collect::<Vec<_>>();
+        let q = self.acc.batch_del(&pairs).expect("not a member");
+        self.u = u;
+
+        q
+    }
+
+    fn check_index(&self, i: usize) -> Result<(), VectorCommitmentError> {
+        if i < self.pos {
+            Ok(())
+        } else {
+            Err(VectorCommitmentError::IndexOutOfBounds {
+                index: i,
+                len: self.pos,
+            })
+        }
+    }
+
+    /// Checks that the positions of `updates` are in bounds and distinct, and that all of them
+    /// hold their old values, also those that do not change.
+    pub(super) fn check_updates(
+        &self,
+        updates: &[(usize, bool, bool)],
+    ) -> Result<(), VectorCommitmentError> {
+        let mut seen = HashSet::new();
+        for (i, b_prime, _) in updates {
+            self.check_index(*i)?;
+            if !seen.insert(*i) {
+                return Err(VectorCommitmentError::DuplicateIndex { index: *i });
+            }
+            if self.acc.contains(&index_prime(*i)) != *b_prime {
+                return Err(VectorCommitmentError::ValueMismatch);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<A: UniversalAccumulator + BatchedAccumulator + MaybeSync> StaticVectorCommitment
     for BinaryVectorCommitment<A>
 {
     type Domain = bool;
@@ -44,39 +424,51 @@ impl<A: UniversalAccumulator + BatchedAccumulator> StaticVectorCommitment
         G: PrimeGroup,
         R: CryptoRng + Rng,
     {
+        let mut acc = A::setup::<G, _>(rng, lambda);
+        let u = acc.state().clone();
+        // the length is bound into the state, by accumulating a prime for it
+        acc.add(&map_len_to_p(0));
+
         BinaryVectorCommitment {
             lambda,
             n,
-            acc: A::setup::<G, _>(rng, lambda),
+            acc,
+            u,
             pos: 0,
         }
     }
 
-    fn commit(&mut self, m: &[Self::Domain]) {
-        let primes = m
-            .iter()
-            .enumerate()
-            .filter(|(_, &m_i)| m_i)
-            .map(|(i, _)| map_i_to_p_i(self.pos + i))
-            .collect::<Vec<_>>();
+    fn commit(&mut self, m: &[Self::Domain]) -> Result<(), VectorCommitmentError> {
+        if m.is_empty() {
+            return Ok(());
+        }
 
-        self.pos += m.len();
-        self.acc.batch_add(&primes);
+        self.append(m).map(|_| ())
     }
 
-    fn open(&self, b: &Self::Domain, i: usize) -> Self::Commitment {
-        let p_i = map_i_to_p_i(i);
+    fn open(&self, b: &Self::Domain, i: usize) -> Result<Self::Commitment, VectorCommitmentError> {
+        self.check_index(i)?;
+        let p_i = index_prime(i);
 
         if *b {
-            Commitment::Mem(self.acc.mem_wit_create(&p_i))
+            if !self.acc.contains(&p_i) {
+                return Err(VectorCommitmentError::ValueMismatch);
+            }
+            Ok(Commitment::Mem(self.acc.mem_wit_create(&p_i)))
         } else {
-            let p = self.acc.non_mem_wit_create(&p_i);
-            Commitment::NonMem(p)
+            let p = self
+                .acc
+                .non_mem_wit_create(&p_i)
+                .ok_or(VectorCommitmentError::ValueMismatch)?;
+            Ok(Commitment::NonMem(p))
         }
     }
 
     fn verify(&self, b: &Self::Domain, i: usize, pi: &Self::Commitment) -> bool {
-        let p_i = map_i_to_p_i(i);
+        if i >= self.pos {
+            return false;
+        }
+        let p_i = index_prime(i);
 
         if *b {
             match pi {
@@ -91,116 +483,280 @@ impl<A: UniversalAccumulator + BatchedAccumulator> StaticVectorCommitment
         }
     }
 
-    fn batch_open(&self, b: &[Self::Domain], i: &[usize]) -> Self::BatchCommitment {
-        debug_assert!(b.len() == i.len());
-
-        let ones = b
-            .iter()
-            .enumerate()
-            .filter(|(_, b_j)| **b_j)
-            .map(|(j, _)| j);
+    fn batch_open(
+        &self,
+        b: &[Self::Domain],
+        i: &[usize],
+    ) -> Result<Self::BatchCommitment, VectorCommitmentError> {
+        check_lengths(b, i)?;
+        for i_j in i {
+            self.check_index(*i_j)?;
+        }
 
-        let zeros = b
-            .iter()
-            .enumerate()
-            .filter(|(_, b_j)| !*b_j)
-            .map(|(j, _)| j);
+        open_bits(&self.acc, b, &index_primes(i))
+    }
 
-        let mut p_ones = BigUint::one();
-        for j in ones {
-            p_ones *= map_i_to_p_i(i[j]);
+    fn batch_verify(&self, b: &[Self::Domain], i: &[usize], pi: &Self::BatchCommitment) -> bool {
+        if b.len() != i.len() || i.iter().any(|i_j| *i_j >= self.pos) {
+            return false;
         }
 
-        let pi_i = if p_ones.is_one() {
-            (BigUint::zero(), BigUint::zero())
-        } else {
-            self.acc.mem_wit_create_star(&p_ones)
-        };
+        verify_bits(&self.acc, b, &index_primes(i), pi)
+    }
 
-        let mut p_zeros = BigUint::one();
-        for j in zeros {
-            p_zeros *= map_i_to_p_i(i[j]);
-        }
+    fn state(&self) -> &BigUint {
+        self.acc.state()
+    }
 
-        let pi_e = if p_zeros.is_one() {
-            (
-                BigUint::zero(),
-                BigUint::zero(),
-                (BigUint::zero(), BigUint::zero(), BigInt::zero()),
-                BigUint::zero(),
-            )
+    fn len(&self) -> usize {
+        self.pos
+    }
+
+    fn capacity(&self) -> usize {
+        self.n
+    }
+}
+
+impl<A: UniversalAccumulator + BatchedAccumulator + MaybeSync> DynamicVectorCommitment
+    for BinaryVectorCommitment<A>
+{
+    fn update(
+        &mut self,
+        b: &Self::Domain,
+        b_prime: &Self::Domain,
+        i: usize,
+    ) -> Result<(), VectorCommitmentError> {
+        self.check_updates(&[(i, *b_prime, *b)])?;
+
+        if b == b_prime {
+            // Nothing to do
+        } else if *b {
+            self.add_primes(&[index_prime(i)]);
         } else {
-            self.acc.non_mem_wit_create_star(&p_zeros)
-        };
+            self.delete_primes(&[index_prime(i)]);
+        }
 
-        BatchCommitment(pi_i, pi_e)
+        Ok(())
     }
+}
 
-    fn batch_verify(&self, b: &[Self::Domain], i: &[usize], pi: &Self::BatchCommitment) -> bool {
-        debug_assert!(b.len() == i.len());
+/// Opens the bits `b`, at the positions with primes `ps`, in `acc`. Fails with
+/// [VectorCommitmentError::ValueMismatch] if a position does not hold its bit.
+pub(super) fn open_bits<A: UniversalAccumulator + BatchedAccumulator + MaybeSync>(
+    acc: &A,
+    b: &[bool],
+    ps: &[BigUint],
+) -> Result<BatchCommitment, VectorCommitmentError> {
+    check_lengths(b, ps)?;
+    let (p_ones, p_zeros) = partition_primes(b, ps);
+    if !acc.contains(&p_ones) {
+        return Err(VectorCommitmentError::ValueMismatch);
+    }
 
-        let ones = b
-            .iter()
-            .enumerate()
-            .filter(|(_, b_j)| **b_j)
-            .map(|(j, _)| j);
+    // both witnesses are independent, so they can be computed in parallel, and the empty
+    // products have the trivial witnesses A and (g, 0)
+    let (w, non_mem) = parallel::join(
+        || {
+            if p_ones.is_one() {
+                acc.state().clone()
+            } else {
+                acc.mem_wit_create(&p_ones)
+            }
+        },
+        || {
+            if p_zeros.is_one() {
+                Some((acc.generator().clone(), BigInt::zero()))
+            } else {
+                acc.non_mem_wit_create(&p_zeros)
+            }
+        },
+    );
+    let (d, b) = non_mem.ok_or(VectorCommitmentError::ValueMismatch)?;
 
-        let mut p_ones = BigUint::one();
-        for j in ones {
-            p_ones *= map_i_to_p_i(i[j]);
-        }
+    Ok(prove_witness_primes(
+        acc.modulus(),
+        acc.state(),
+        &BatchWitness { w, d, b },
+        (p_ones, p_zeros),
+    ))
+}
 
-        if !p_ones.is_one() && !self.acc.ver_mem_star(&p_ones, &pi.0) {
-            return false;
-        }
+/// Updates the witness `wit` for the products of the primes set to `1` and `0`, after the primes
+/// with product `added` were added and those with product `deleted` deleted, changing the state
+/// from `old_state` to `new_state`.
+pub(super) fn update_witness_primes(
+    n: &BigUint,
+    new_state: &BigUint,
+    wit: &BatchWitness,
+    (p_ones, p_zeros): (BigUint, BigUint),
+    old_state: &BigUint,
+    (added, deleted): (&BigUint, &BigUint),
+) -> Option<BatchWitness> {
+    // the witnesses of empty products are trivial, and not part of the proofs
+    let w = if p_ones.is_one() {
+        new_state.clone()
+    } else {
+        mem_wit_update(&wit.w, &p_ones, added, deleted, new_state, n)?
+    };
+    let (d, b) = if p_zeros.is_one() {
+        (wit.d.clone(), wit.b.clone())
+    } else {
+        non_mem_wit_update(
+            &(wit.d.clone(), wit.b.clone()),
+            &p_zeros,
+            added,
+            deleted,
+            old_state,
+            new_state,
+            n,
+        )?
+    };
 
-        let zeros = b
-            .iter()
-            .enumerate()
-            .filter(|(_, b_j)| !**b_j)
-            .map(|(j, _)| j);
+    Some(BatchWitness { w, d, b })
+}
 
-        let mut p_zeros = BigUint::one();
-        for j in zeros {
-            p_zeros *= map_i_to_p_i(i[j]);
-        }
+/// Creates the batch opening from the witness `wit`, for the products of the primes set to `1`
+/// and `0`, in the state `root`.
+pub(super) fn prove_witness_primes(
+    n: &BigUint,
+    root: &BigUint,
+    wit: &BatchWitness,
+    (p_ones, p_zeros): (BigUint, BigUint),
+) -> BatchCommitment {
+    let (pi_i, pi_e) = parallel::join(
+        || {
+            if p_ones.is_one() {
+                (BigUint::zero(), BigUint::zero())
+            } else {
+                let q = proofs::ni_poe_prove(&p_ones, &wit.w, root, n);
+                (wit.w.clone(), q)
+            }
+        },
+        || {
+            if p_zeros.is_one() {
+                empty_non_mem_proof()
+            } else {
+                non_mem_wit_star(&p_zeros, &(wit.d.clone(), wit.b.clone()), root, n)
+            }
+        },
+    );
 
-        if !p_zeros.is_one() && !self.acc.ver_non_mem_star(&p_zeros, &pi.1) {
-            return false;
-        }
+    BatchCommitment(pi_i, pi_e, wit.b.clone())
+}
 
-        true
-    }
+/// The placeholder for a non-membership proof of the empty product, which is never verified.
+fn empty_non_mem_proof() -> (BigUint, BigUint, (BigUint, BigUint, BigInt), BigUint) {
+    (
+        BigUint::zero(),
+        BigUint::zero(),
+        (BigUint::zero(), BigUint::zero(), BigInt::zero()),
+        BigUint::zero(),
+    )
+}
 
-    fn state(&self) -> &BigUint {
-        self.acc.state()
+/// Verifies the opening of the bits `b`, at the positions with primes `ps`, in `acc`.
+pub(super) fn verify_bits<A: UniversalAccumulator + BatchedAccumulator + MaybeSync>(
+    acc: &A,
+    b: &[bool],
+    ps: &[BigUint],
+    pi: &BatchCommitment,
+) -> bool {
+    if b.len() != ps.len() {
+        return false;
     }
+    let (p_ones, p_zeros) = partition_primes(b, ps);
+
+    let (valid_ones, valid_zeros) = parallel::join(
+        || p_ones.is_one() || acc.ver_mem_star(&p_ones, &pi.0),
+        || p_zeros.is_one() || acc.ver_non_mem_star(&p_zeros, &pi.1),
+    );
+
+    valid_ones && valid_zeros
 }
 
-impl<A: UniversalAccumulator + BatchedAccumulator> DynamicVectorCommitment
-    for BinaryVectorCommitment<A>
-{
-    fn update(&mut self, b: &Self::Domain, b_prime: &Self::Domain, i: usize) {
-        if b == b_prime {
-            // Nothing to do
-        } else if *b {
-            self.acc.add(&map_i_to_p_i(i));
+/// Returns the products of the primes of all positions set to `1`, and set to `0`.
+pub(super) fn partition_primes(b: &[bool], ps: &[BigUint]) -> (BigUint, BigUint) {
+    let mut ones = Vec::new();
+    let mut zeros = Vec::new();
+    for (b_j, p_j) in b.iter().zip(ps) {
+        if *b_j {
+            ones.push(p_j.clone());
         } else {
-            self.acc.del(&map_i_to_p_i(i)).expect("not a member");
+            zeros.push(p_j.clone());
         }
     }
+
+    parallel::join(|| product(&ones), || product(&zeros))
 }
 
-fn map_i_to_p_i(i: usize) -> BigUint {
-    let mut to_hash = [0u8; 8];
-    BigEndian::write_u64(&mut to_hash, i as u64);
+/// Checks that there are as many `values` as `positions`, which would otherwise be truncated to the
+/// shorter of both.
+pub(super) fn check_lengths<T, U>(
+    values: &[T],
+    positions: &[U],
+) -> Result<(), VectorCommitmentError> {
+    if values.len() != positions.len() {
+        return Err(VectorCommitmentError::LengthMismatch {
+            values: values.len(),
+            positions: positions.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the primes added and deleted by `updates`, skipping unchanged positions.
+fn batch_update_primes(updates: &[(usize, bool, bool)]) -> (Vec<BigUint>, Vec<BigUint>) {
+    let mut added = Vec::new();
+    let mut deleted = Vec::new();
+    for (i, b_prime, b) in updates {
+        match (b_prime, b) {
+            (false, true) => added.push(index_prime(*i)),
+            (true, false) => deleted.push(index_prime(*i)),
+            _ => {}
+        }
+    }
+
+    (added, deleted)
+}
+
+/// Returns the products of the primes added and deleted by the update `u`.
+fn update_primes(u: &VectorUpdate<bool>) -> (BigUint, BigUint) {
+    if u.b == u.b_prime {
+        (BigUint::one(), BigUint::one())
+    } else if u.b {
+        (index_prime(u.i), BigUint::one())
+    } else {
+        (BigUint::one(), index_prime(u.i))
+    }
+}
+
+/// Returns the primes added when appending `m` to a vector of length `len`, the primes of all
+/// set entries followed by the prime of the new length.
+fn append_primes(len: usize, m: &[bool]) -> Vec<BigUint> {
+    let mut primes = m
+        .iter()
+        .enumerate()
+        .filter(|(_, &m_i)| m_i)
+        .map(|(i, _)| index_prime(len + i))
+        .collect::<Vec<_>>();
+    primes.push(map_len_to_p(len + m.len()));
+
+    primes
+}
+
+/// Maps the length of the vector to a prime, which is below all index primes, see [index_prime].
+fn map_len_to_p(len: usize) -> BigUint {
+    let mut to_hash = [0u8; 14];
+    to_hash[..6].copy_from_slice(b"length");
+    BigEndian::write_u64(&mut to_hash[6..], len as u64);
     hash_prime::<_, Blake2b512>(&to_hash)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::accumulator::Accumulator;
+    use crate::accumulator::{Accumulator, ChunkedAccumulator};
     use crate::group::RSAGroup;
     use rand::{Rng, SeedableRng};
     use rand_chacha::ChaChaRng;
@@ -219,18 +775,43 @@ mod tests {
         val[2] = true;
         val[3] = false;
 
-        vc.commit(&val);
+        vc.commit(&val).unwrap();
 
         // open a set bit
-        let comm = vc.open(&true, 2);
+        let comm = vc.open(&true, 2).unwrap();
         assert!(vc.verify(&true, 2, &comm), "invalid commitment (bit set)");
 
         // open a set bit
-        let comm = vc.open(&false, 3);
+        let comm = vc.open(&false, 3).unwrap();
         assert!(
             vc.verify(&false, 3, &comm),
             "invalid commitment (bit not set)"
         );
+
+        // a set bit can not be opened to 0
+        assert_eq!(
+            vc.open(&false, 2),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.batch_open(&[false, false], &[3, 2]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.open_witness(&[false], &[2]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+
+        // neither can an unset bit be opened to 1, alone or together with set bits
+        assert_eq!(vc.open(&true, 3), Err(VectorCommitmentError::ValueMismatch));
+        assert_eq!(
+            vc.batch_open(&[true, true], &[2, 3]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.open_witness(&[true], &[3]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
     }
 
     #[test]
@@ -243,14 +824,26 @@ mod tests {
             BinaryVectorCommitment::<Accumulator>::setup::<RSAGroup, _>(&mut rng, lambda, n);
 
         let val: Vec<bool> = (0..64).map(|_| rng.gen()).collect();
-        vc.commit(&val);
+        vc.commit(&val).unwrap();
 
         let committed = vec![val[2].clone(), val[3].clone(), val[9].clone()];
-        let comm = vc.batch_open(&committed, &[2, 3, 9]);
+        let comm = vc.batch_open(&committed, &[2, 3, 9]).unwrap();
         assert!(
             vc.batch_verify(&committed, &[2, 3, 9], &comm),
             "invalid commitment (bit set)"
         );
+
+        // values and positions of different lengths are not truncated to the shorter
+        let mismatch = VectorCommitmentError::LengthMismatch {
+            values: 3,
+            positions: 2,
+        };
+        assert_eq!(vc.batch_open(&committed, &[2, 3]), Err(mismatch.clone()));
+        assert_eq!(vc.open_witness(&committed, &[2, 3]), Err(mismatch));
+        let comm = vc.batch_open(&committed[..2], &[2, 3]).unwrap();
+        assert!(vc.batch_verify(&committed[..2], &[2, 3], &comm));
+        assert!(!vc.batch_verify(&committed, &[2, 3], &comm));
+        assert!(!vc.batch_verify(&committed[..2], &[2, 3, 9], &comm));
     }
 
     #[test]
@@ -267,12 +860,12 @@ mod tests {
         val[2] = true;
         val[3] = false;
 
-        vc.commit(&val);
+        vc.commit(&val).unwrap();
 
-        let comm = vc.open(&true, 2);
+        let comm = vc.open(&true, 2).unwrap();
         assert!(vc.verify(&true, 2, &comm), "invalid commitment (bit set)");
 
-        vc.update(&false, &true, 2);
+        vc.update(&false, &true, 2).unwrap();
 
         // ensure old commitment fails now
         assert!(
@@ -280,10 +873,284 @@ mod tests {
             "commitment should be invalid (bit set)"
         );
 
-        let comm_new = vc.open(&false, 2);
+        let comm_new = vc.open(&false, 2).unwrap();
         assert!(
             vc.verify(&false, 2, &comm_new),
             "invalid commitment (bit not set)"
         );
     }
+
+    #[test]
+    fn test_binary_vc_len() {
+        let lambda = 128;
+        let n = 100;
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc =
+            BinaryVectorCommitment::<Accumulator>::setup::<RSAGroup, _>(&mut rng, lambda, n);
+        assert!(vc.is_empty());
+        assert!(vc.verify_len(0, &vc.prove_len()));
+
+        let val: Vec<bool> = (0..64).map(|_| rng.gen()).collect();
+        vc.commit(&val).unwrap();
+        assert_eq!(vc.len(), 64);
+        assert_eq!(vc.capacity(), 100);
+
+        let pi = vc.prove_len();
+        assert!(vc.verify_len(64, &pi));
+        assert!(!vc.verify_len(0, &pi));
+        assert!(!vc.verify_len(65, &pi));
+
+        // indices past the end can not be opened, not even as zeros
+        assert_eq!(
+            vc.open(&false, 64),
+            Err(VectorCommitmentError::IndexOutOfBounds { index: 64, len: 64 })
+        );
+        let forged = Commitment::NonMem(vc.acc.non_mem_wit_create(&index_prime(64)).unwrap());
+        assert!(!vc.verify(&false, 64, &forged));
+        assert!(vc.update(&true, &false, 64).is_err());
+
+        assert_eq!(
+            vc.commit(&val),
+            Err(VectorCommitmentError::CapacityExceeded {
+                len: 64,
+                additional: 64,
+                capacity: 100
+            })
+        );
+        vc.commit(&val[..36]).unwrap();
+        assert_eq!(vc.len(), 100);
+        assert!(vc.verify_len(100, &vc.prove_len()));
+    }
+
+    #[test]
+    fn test_binary_vc_chunked() {
+        let lambda = 128;
+        let n = 64;
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc =
+            BinaryVectorCommitment::<ChunkedAccumulator>::setup::<RSAGroup, _>(&mut rng, lambda, n);
+        let mut val: Vec<bool> = (0..32).map(|_| rng.gen()).collect();
+        vc.commit(&val).unwrap();
+
+        for step in 0..8 {
+            if step % 3 == 0 {
+                let m: Vec<bool> = (0..4).map(|_| rng.gen()).collect();
+                vc.append(&m).unwrap();
+                val.extend(m);
+            } else {
+                let i = rng.gen_range(0..val.len());
+                vc.update(&!val[i], &val[i], i).unwrap();
+                val[i] = !val[i];
+            }
+
+            // u is the state without the length prime
+            let p_len = map_len_to_p(vc.len());
+            assert_eq!(vc.prove_len(), vc.acc.mem_wit_create(&p_len));
+            assert!(vc.verify_len(val.len(), &vc.prove_len()));
+        }
+
+        let is = (0..val.len()).collect::<Vec<_>>();
+        let pi = vc.batch_open(&val, &is).unwrap();
+        assert!(vc.batch_verify(&val, &is, &pi));
+    }
+
+    #[test]
+    fn test_binary_vc_append() {
+        let lambda = 128;
+        let n = 1024;
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc =
+            BinaryVectorCommitment::<Accumulator>::setup::<RSAGroup, _>(&mut rng, lambda, n);
+        vc.commit(&(0..32).map(|_| rng.gen()).collect::<Vec<bool>>())
+            .unwrap();
+
+        let old_state = vc.state().clone();
+        let old_len = vc.len();
+
+        let m: Vec<bool> = (0..16).map(|_| rng.gen()).collect();
+        let pi = vc.append(&m).unwrap();
+        let modulus = vc.modulus().clone();
+        let verify = |old_state: &BigUint, old_len, new_state: &BigUint, m: &[bool], pi| {
+            BinaryVectorCommitment::<Accumulator>::verify_append(
+                &modulus, old_state, old_len, new_state, m, pi,
+            )
+        };
+        assert!(verify(&old_state, old_len, vc.state(), &m, &pi));
+
+        // different entries, or a different old or new state
+        let mut m_bad = m.clone();
+        m_bad[3] = !m_bad[3];
+        assert!(!verify(&old_state, old_len, vc.state(), &m_bad, &pi));
+        assert!(!verify(&old_state, old_len, vc.state(), &m[1..], &pi));
+        assert!(!verify(&old_state, old_len + 1, vc.state(), &m, &pi));
+        assert!(!verify(vc.state(), old_len, vc.state(), &m, &pi));
+        assert!(!verify(&old_state, old_len, &old_state, &m, &pi));
+
+        // empty appends
+        let old_state = vc.state().clone();
+        let pi = vc.append(&[]).unwrap();
+        assert!(verify(&old_state, old_len + 16, vc.state(), &[], &pi));
+
+        let comm = vc.open(&m[5], old_len + 5).unwrap();
+        assert!(vc.verify(&m[5], old_len + 5, &comm));
+    }
+
+    #[test]
+    fn test_binary_vc_update_opening() {
+        let lambda = 128;
+        let n = 1024;
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc =
+            BinaryVectorCommitment::<Accumulator>::setup::<RSAGroup, _>(&mut rng, lambda, n);
+        let val = vec![true, false, true, false, true, false];
+        vc.commit(&val).unwrap();
+
+        let mut opens = val
+            .iter()
+            .enumerate()
+            .map(|(j, b)| vc.open(b, j).unwrap())
+            .collect::<Vec<_>>();
+        let mut wit = vc.open_witness(&val[..4], &[0, 1, 2, 3]).unwrap();
+        let mut batch = vc.batch_open(&val[..4], &[0, 1, 2, 3]).unwrap();
+        let mut ones = vc.batch_open(&[true, true], &[0, 2]).unwrap();
+        let mut cur = val.clone();
+        type Vc = BinaryVectorCommitment<Accumulator>;
+
+        // set position 5, then clear position 4
+        for (b, b_prime, i) in [(true, false, 5), (false, true, 4)] {
+            let old_state = vc.state().clone();
+            vc.update(&b, &b_prime, i).unwrap();
+            let u = VectorUpdate {
+                old_state,
+                b,
+                b_prime,
+                i,
+            };
+            let (n, a_new) = (vc.modulus(), vc.state());
+
+            for (j, pi) in opens.iter_mut().enumerate() {
+                match Vc::update_opening(n, a_new, pi, j, &u) {
+                    Some(pi_new) => {
+                        assert!(vc.verify(&cur[j], j, &pi_new), "invalid update");
+                        *pi = pi_new;
+                    }
+                    None => {
+                        assert_eq!(j, i);
+                        cur[j] = b;
+                        *pi = vc.open(&b, j).unwrap();
+                    }
+                }
+            }
+
+            wit = Vc::update_witness(n, a_new, &wit, &val[..4], &[0, 1, 2, 3], &u).unwrap();
+            let pi = vc.prove_witness(&wit, &val[..4], &[0, 1, 2, 3]).unwrap();
+            assert!(vc.batch_verify(&val[..4], &[0, 1, 2, 3], &pi));
+
+            // the openings themselves can be updated, also without any zeros
+            assert!(!vc.batch_verify(&val[..4], &[0, 1, 2, 3], &batch));
+            batch =
+                Vc::update_batch_opening(n, a_new, &batch, &val[..4], &[0, 1, 2, 3], &u).unwrap();
+            assert!(vc.batch_verify(&val[..4], &[0, 1, 2, 3], &batch));
+            ones = Vc::update_batch_opening(n, a_new, &ones, &[true, true], &[0, 2], &u).unwrap();
+            assert!(vc.batch_verify(&[true, true], &[0, 2], &ones));
+        }
+
+        // the witness covers position 4 now
+        let wit = vc.open_witness(&[false], &[4]).unwrap();
+        let batch = vc.batch_open(&[false], &[4]).unwrap();
+        let u = VectorUpdate {
+            old_state: vc.state().clone(),
+            b: true,
+            b_prime: false,
+            i: 4,
+        };
+        vc.update(&true, &false, 4).unwrap();
+        let (n, a_new) = (vc.modulus(), vc.state());
+        assert!(Vc::update_witness(n, a_new, &wit, &[false], &[4], &u).is_none());
+        assert!(Vc::update_batch_opening(n, a_new, &batch, &[false], &[4], &u).is_none());
+    }
+
+    #[test]
+    fn test_binary_vc_batch_update() {
+        let lambda = 128;
+        let n = 1024;
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc =
+            BinaryVectorCommitment::<Accumulator>::setup::<RSAGroup, _>(&mut rng, lambda, n);
+        let val = vec![true, true, false, false, true, false];
+        vc.commit(&val).unwrap();
+
+        let old_state = vc.state().clone();
+        let updates = [
+            (0, true, false),
+            (2, false, true),
+            (3, false, false),
+            (4, true, false),
+        ];
+        let pi = vc.batch_update(&updates).unwrap();
+        assert!(vc.verify_batch_update(&old_state, &updates, &pi));
+        assert!(!vc.verify_batch_update(&old_state, &updates[1..], &pi));
+        assert!(!vc.verify_batch_update(vc.state(), &updates, &pi));
+
+        let new_val = [false, true, true, false, false, false];
+        for (i, b) in new_val.iter().enumerate() {
+            let comm = vc.open(b, i).unwrap();
+            assert!(vc.verify(b, i, &comm), "invalid commitment {}", i);
+        }
+
+        // only additions, or nothing at all
+        let old_state = vc.state().clone();
+        let pi = vc.batch_update(&[(5, false, true)]).unwrap();
+        assert!(vc.verify_batch_update(&old_state, &[(5, false, true)], &pi));
+        let old_state = vc.state().clone();
+        let pi = vc.batch_update(&[]).unwrap();
+        assert!(vc.verify_batch_update(&old_state, &[], &pi));
+
+        assert_eq!(
+            vc.batch_update(&[(6, false, true)]),
+            Err(VectorCommitmentError::IndexOutOfBounds { index: 6, len: 6 })
+        );
+
+        // duplicate or stale entries change nothing
+        let state = vc.state().clone();
+        assert_eq!(
+            vc.batch_update(&[(0, false, true), (0, false, true)]),
+            Err(VectorCommitmentError::DuplicateIndex { index: 0 })
+        );
+        assert_eq!(
+            vc.batch_update(&[(0, true, false)]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.batch_update(&[(0, false, true), (1, false, true)]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.update(&true, &false, 1),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(vc.state(), &state);
+
+        let updates = [(0, false, true)];
+        let pi = vc.batch_update(&updates).unwrap();
+        assert!(vc.verify_batch_update(&state, &updates, &pi));
+        assert!(!vc.verify_batch_update(&state, &[(0, false, true), (0, false, true)], &pi));
+
+        // adding the prime of a position which is already set
+        let mut forged = vc.clone();
+        let old_state = forged.state().clone();
+        let q_add = forged.acc.batch_add(&[index_prime(1)]);
+        let pi = UpdateProof {
+            u: old_state.clone(),
+            q_del: BigUint::one(),
+            q_add,
+            non_mem: empty_non_mem_proof(),
+        };
+        assert!(!forged.verify_batch_update(&old_state, &[(1, false, true)], &pi));
+    }
 }
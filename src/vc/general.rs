@@ -1,13 +1,26 @@
+use std::collections::HashSet;
+
 use bitvec::prelude::*;
 use blake2::{Blake2b512, Digest};
 use num_bigint::BigUint;
 use rand::rngs::OsRng;
 use rand::{CryptoRng, Rng};
 
+use crate::error::VectorCommitmentError;
+use crate::math::product;
 use crate::traits::*;
-use crate::vc::BinaryVectorCommitment;
-
-pub fn create_vector_commitment<A: UniversalAccumulator + BatchedAccumulator, G: PrimeGroup>(
+use crate::vc::binary::{
+    check_lengths, partition_primes, prove_witness_primes, update_witness_primes,
+};
+use crate::vc::{
+    index_prime, index_primes, AppendProof, BatchCommitment, BatchWitness, BinaryVectorCommitment,
+    UpdateProof, VectorUpdate,
+};
+
+pub fn create_vector_commitment<
+    A: UniversalAccumulator + BatchedAccumulator + MaybeSync,
+    G: PrimeGroup,
+>(
     lambda: usize,
     n: usize,
 ) -> VectorCommitment<A> {
@@ -15,15 +28,323 @@ pub fn create_vector_commitment<A: UniversalAccumulator + BatchedAccumulator, G:
     VectorCommitment::<A>::setup::<G, _>(&mut rng, lambda, n)
 }
 
+/// How values are encoded into the bits of their entry.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// The bits of `Blake2b(value)`, truncated to `lambda` bits, rounded down to whole bytes and
+    /// at most the 512 bits of the hash. Openings only bind the hash.
+    Hashed,
+    /// The bits of the value itself, big endian, which must fit into `width` bits, independent
+    /// of `lambda`. Openings reveal the value, e.g. a `u64` with `width = 64`.
+    Raw { width: usize },
+}
+
+impl Encoding {
+    /// Returns the number of bits of an entry, for the security parameter `lambda`.
+    pub fn width(&self, lambda: usize) -> usize {
+        match self {
+            Encoding::Hashed => hash_width(lambda),
+            Encoding::Raw { width } => *width,
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone)]
 pub struct VectorCommitment<A: UniversalAccumulator + BatchedAccumulator> {
     lambda: usize,
     n: usize,
+    encoding: Encoding,
     vc: BinaryVectorCommitment<A>,
 }
 
-impl<A: UniversalAccumulator + BatchedAccumulator> StaticVectorCommitment for VectorCommitment<A> {
+impl<A: UniversalAccumulator + BatchedAccumulator + MaybeSync> VectorCommitment<A> {
+    /// Creates an empty vector commitment, for up to `n` entries encoded with `encoding`, in a
+    /// group for the security parameter `lambda`.
+    pub fn setup_with_encoding<G, R>(
+        rng: &mut R,
+        lambda: usize,
+        n: usize,
+        encoding: Encoding,
+    ) -> Self
+    where
+        G: PrimeGroup,
+        R: CryptoRng + Rng,
+    {
+        VectorCommitment {
+            lambda,
+            n,
+            encoding,
+            vc: BinaryVectorCommitment::<A>::setup::<G, _>(rng, lambda, n * encoding.width(lambda)),
+        }
+    }
+
+    /// Returns the encoding of the values.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the modulus of the group.
+    pub fn modulus(&self) -> &BigUint {
+        self.vc.modulus()
+    }
+
+    /// Returns the number of bits of an entry, see [Encoding::width].
+    pub fn width(&self) -> usize {
+        self.encoding.width(self.lambda)
+    }
+
+    /// Proves the length of the vector, see [BinaryVectorCommitment::prove_len].
+    pub fn prove_len(&self) -> BigUint {
+        self.vc.prove_len()
+    }
+
+    /// Verifies that the committed vector has length `len`.
+    pub fn verify_len(&self, len: usize, pi: &BigUint) -> bool {
+        self.vc.verify_len(len * self.width(), pi)
+    }
+
+    /// Appends `ms` to the committed vector, see [BinaryVectorCommitment::append].
+    pub fn append(&mut self, ms: &[BigUint]) -> Result<AppendProof, VectorCommitmentError> {
+        self.check_capacity(ms.len())?;
+
+        self.vc.append(&self.encode(ms)?)
+    }
+
+    /// Verifies that the vector with state `new_state` extends the vector with state `old_state`
+    /// and length `old_len` by exactly `ms`, for entries encoded with `encoding` and the security
+    /// parameter `lambda`, in the group with modulus `modulus`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_append(
+        modulus: &BigUint,
+        encoding: Encoding,
+        lambda: usize,
+        old_state: &BigUint,
+        old_len: usize,
+        new_state: &BigUint,
+        ms: &[BigUint],
+        pi: &AppendProof,
+    ) -> bool {
+        match encode(ms, encoding, lambda) {
+            Ok(m) => BinaryVectorCommitment::<A>::verify_append(
+                modulus,
+                old_state,
+                old_len * encoding.width(lambda),
+                new_state,
+                &m,
+                pi,
+            ),
+            Err(_) => false,
+        }
+    }
+
+    /// Changes the values of many positions at once, given as `(i, b_prime, b)` for a change of
+    /// position `i` from `b_prime` to `b`, with a single proof. Returns an error, leaving the
+    /// vector unchanged, if a position is given twice, or does not hold `b_prime`, see
+    /// [BinaryVectorCommitment::batch_update].
+    pub fn batch_update(
+        &mut self,
+        updates: &[(usize, BigUint, BigUint)],
+    ) -> Result<UpdateProof, VectorCommitmentError> {
+        let mut seen = HashSet::new();
+        for (i, _, _) in updates {
+            self.check_index(*i)?;
+            if !seen.insert(*i) {
+                return Err(VectorCommitmentError::DuplicateIndex { index: *i });
+            }
+        }
+        // all bits of the old values are checked, not only those that change
+        let bits = self.update_bits(updates)?;
+
+        self.vc.batch_update(&bits)
+    }
+
+    /// Verifies that the committed vector is the vector with state `old_state`, changed by
+    /// `updates`.
+    pub fn verify_batch_update(
+        &self,
+        old_state: &BigUint,
+        updates: &[(usize, BigUint, BigUint)],
+        pi: &UpdateProof,
+    ) -> bool {
+        let mut seen = HashSet::new();
+        if updates
+            .iter()
+            .any(|(i, _, _)| *i >= self.len() || !seen.insert(*i))
+        {
+            return false;
+        }
+
+        match self.update_bits(updates) {
+            Ok(bits) => self.vc.verify_batch_update(old_state, &bits, pi),
+            Err(_) => false,
+        }
+    }
+
+    /// Opens `b` at positions `is`, like [StaticVectorCommitment::batch_open], but returns the
+    /// witness, see [BinaryVectorCommitment::open_witness].
+    pub fn open_witness(
+        &self,
+        b: &[BigUint],
+        is: &[usize],
+    ) -> Result<BatchWitness, VectorCommitmentError> {
+        for i in is {
+            self.check_index(*i)?;
+        }
+        let (comm, comm_is) = self.all_bits(b, is)?;
+
+        self.vc.open_witness(&comm, &comm_is)
+    }
+
+    /// Updates the witness `wit` for `b` at positions `is`, created before the update `u`, to the
+    /// state `new_state` after it, for entries encoded with `encoding` and the security parameter
+    /// `lambda`, in the group with modulus `modulus`. Returns `None` if the update changed one of
+    /// the positions, or `b` and `is` differ in length.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_witness(
+        modulus: &BigUint,
+        encoding: Encoding,
+        lambda: usize,
+        new_state: &BigUint,
+        wit: &BatchWitness,
+        b: &[BigUint],
+        is: &[usize],
+        u: &VectorUpdate<BigUint>,
+    ) -> Option<BatchWitness> {
+        if is.contains(&u.i) && u.b != u.b_prime {
+            return None;
+        }
+
+        let (added, deleted) = update_primes(u, encoding, lambda).ok()?;
+        let (comm, comm_is) = all_bits(b, is, encoding, lambda).ok()?;
+
+        update_witness_primes(
+            modulus,
+            new_state,
+            wit,
+            partition_primes(&comm, &index_primes(&comm_is)),
+            &u.old_state,
+            (&added, &deleted),
+        )
+    }
+
+    /// Updates the batch opening `pi` of `b` at positions `is`, created before the update `u`,
+    /// like [VectorCommitment::update_witness]. Returns `None` if the update changed one of the
+    /// positions, or `b` and `is` differ in length.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_batch_opening(
+        modulus: &BigUint,
+        encoding: Encoding,
+        lambda: usize,
+        new_state: &BigUint,
+        pi: &BatchCommitment,
+        b: &[BigUint],
+        is: &[usize],
+        u: &VectorUpdate<BigUint>,
+    ) -> Option<BatchCommitment> {
+        let wit = Self::update_witness(
+            modulus,
+            encoding,
+            lambda,
+            new_state,
+            &pi.witness(),
+            b,
+            is,
+            u,
+        )?;
+        let (comm, comm_is) = all_bits(b, is, encoding, lambda).ok()?;
+
+        Some(prove_witness_primes(
+            modulus,
+            new_state,
+            &wit,
+            partition_primes(&comm, &index_primes(&comm_is)),
+        ))
+    }
+
+    /// Creates the batch opening for `b` at positions `is`, from their witness.
+    pub fn prove_witness(
+        &self,
+        wit: &BatchWitness,
+        b: &[BigUint],
+        is: &[usize],
+    ) -> Result<BatchCommitment, VectorCommitmentError> {
+        let (comm, comm_is) = self.all_bits(b, is)?;
+
+        self.vc.prove_witness(wit, &comm, &comm_is)
+    }
+
+    /// Returns all bits of `updates`, as updates of the binary vector, including the unchanged
+    /// ones, such that their old values are checked as well.
+    fn update_bits(
+        &self,
+        updates: &[(usize, BigUint, BigUint)],
+    ) -> Result<Vec<(usize, bool, bool)>, VectorCommitmentError> {
+        let mut bits = Vec::new();
+        for (i, b_prime, b) in updates {
+            let (comm_prime, is) = self.bits(b_prime, *i)?;
+            let (comm, _) = self.bits(b, *i)?;
+            bits.extend(
+                is.into_iter()
+                    .zip(comm_prime.into_iter().zip(comm))
+                    .map(|(j, (el_prime, el))| (j, el_prime, el)),
+            );
+        }
+
+        Ok(bits)
+    }
+
+    /// Returns the concatenated bits of all `ms`.
+    fn encode(&self, ms: &[BigUint]) -> Result<Vec<bool>, VectorCommitmentError> {
+        encode(ms, self.encoding, self.lambda)
+    }
+
+    fn check_capacity(&self, additional: usize) -> Result<(), VectorCommitmentError> {
+        if additional <= self.n - self.len() {
+            Ok(())
+        } else {
+            Err(VectorCommitmentError::CapacityExceeded {
+                len: self.len(),
+                additional,
+                capacity: self.n,
+            })
+        }
+    }
+
+    fn check_index(&self, i: usize) -> Result<(), VectorCommitmentError> {
+        if i < self.len() {
+            Ok(())
+        } else {
+            Err(VectorCommitmentError::IndexOutOfBounds {
+                index: i,
+                len: self.len(),
+            })
+        }
+    }
+
+    /// Returns the bits of `b` at position `i`, and their indices in the binary vector.
+    fn bits(
+        &self,
+        b: &BigUint,
+        i: usize,
+    ) -> Result<(Vec<bool>, Vec<usize>), VectorCommitmentError> {
+        bits(b, i, self.encoding, self.lambda)
+    }
+
+    /// Returns the bits of all `b` at positions `is`, and their indices in the binary vector.
+    fn all_bits(
+        &self,
+        b: &[BigUint],
+        is: &[usize],
+    ) -> Result<(Vec<bool>, Vec<usize>), VectorCommitmentError> {
+        all_bits(b, is, self.encoding, self.lambda)
+    }
+}
+
+impl<A: UniversalAccumulator + BatchedAccumulator + MaybeSync> StaticVectorCommitment
+    for VectorCommitment<A>
+{
     type Domain = BigUint;
     type Commitment = <BinaryVectorCommitment<A> as StaticVectorCommitment>::BatchCommitment;
     type BatchCommitment = <BinaryVectorCommitment<A> as StaticVectorCommitment>::BatchCommitment;
@@ -33,101 +354,205 @@ impl<A: UniversalAccumulator + BatchedAccumulator> StaticVectorCommitment for Ve
         G: PrimeGroup,
         R: CryptoRng + Rng,
     {
-        VectorCommitment {
-            lambda,
-            n,
-            vc: BinaryVectorCommitment::<A>::setup::<G, _>(rng, lambda, n),
-        }
+        Self::setup_with_encoding::<G, _>(rng, lambda, n, Encoding::Hashed)
     }
 
     // Internally we map the incoming integers onto a binary vc in the following way
     // ms: [a, b, c]
-    // a' = hash_binary(a), b' ..
+    // a' = hash_binary(a), b' .., or the bits of a, b, .. with Encoding::Raw
     // vc[a'..., b'..., c'...]
-    fn commit(&mut self, ms: &[Self::Domain]) {
-        for m in ms {
-            let comm = hash_binary(m, self.lambda).into_iter().collect::<Vec<_>>();
-            debug_assert!(comm.len() == self.lambda);
-            self.vc.commit(&comm);
-        }
+    fn commit(&mut self, ms: &[Self::Domain]) -> Result<(), VectorCommitmentError> {
+        self.check_capacity(ms.len())?;
+
+        self.vc.commit(&self.encode(ms)?)
     }
 
-    fn open(&self, b: &Self::Domain, i: usize) -> Self::Commitment {
-        let comm = hash_binary(b, self.lambda).into_iter().collect::<Vec<_>>();
-        let offset = i * self.lambda;
-        let is = (0..comm.len()).map(|j| offset + j).collect::<Vec<_>>();
+    fn open(&self, b: &Self::Domain, i: usize) -> Result<Self::Commitment, VectorCommitmentError> {
+        self.check_index(i)?;
+        let (comm, is) = self.bits(b, i)?;
 
         self.vc.batch_open(&comm, &is)
     }
 
     fn verify(&self, b: &Self::Domain, i: usize, pi: &Self::Commitment) -> bool {
-        let comm = hash_binary(b, self.lambda).into_iter().collect::<Vec<_>>();
-        let offset = i * self.lambda;
-        let is = (0..comm.len()).map(|j| offset + j).collect::<Vec<_>>();
-
-        self.vc.batch_verify(&comm, &is, pi)
+        match self.bits(b, i) {
+            Ok((comm, is)) => self.vc.batch_verify(&comm, &is, pi),
+            Err(_) => false,
+        }
     }
 
-    fn batch_open(&self, b: &[Self::Domain], is: &[usize]) -> Self::BatchCommitment {
-        debug_assert!(b.len() == is.len());
-
-        let mut comm = Vec::with_capacity(self.lambda * b.len());
-        let mut comm_is = Vec::with_capacity(self.lambda * is.len());
-
-        for (el, i) in b.iter().zip(is) {
-            let c = hash_binary(el, self.lambda).into_iter().collect::<Vec<_>>();
-            comm.extend(&c);
-            let offset = i * self.lambda;
-            comm_is.extend((0..c.len()).map(|j| offset + j));
+    fn batch_open(
+        &self,
+        b: &[Self::Domain],
+        is: &[usize],
+    ) -> Result<Self::BatchCommitment, VectorCommitmentError> {
+        for i in is {
+            self.check_index(*i)?;
         }
+        let (comm, comm_is) = self.all_bits(b, is)?;
 
         self.vc.batch_open(&comm, &comm_is)
     }
 
     fn batch_verify(&self, b: &[Self::Domain], is: &[usize], pi: &Self::BatchCommitment) -> bool {
-        debug_assert!(b.len() == is.len());
-
-        let mut comm = Vec::with_capacity(self.lambda * b.len());
-        let mut comm_is = Vec::with_capacity(self.lambda * is.len());
-
-        for (el, i) in b.iter().zip(is) {
-            let c = hash_binary(el, self.lambda).into_iter().collect::<Vec<_>>();
-            comm.extend(&c);
-            let offset = i * self.lambda;
-            comm_is.extend((0..c.len()).map(|j| offset + j));
+        match self.all_bits(b, is) {
+            Ok((comm, comm_is)) => self.vc.batch_verify(&comm, &comm_is, pi),
+            Err(_) => false,
         }
-
-        self.vc.batch_verify(&comm, &comm_is, pi)
     }
 
     fn state(&self) -> &BigUint {
         self.vc.state()
     }
+
+    fn len(&self) -> usize {
+        self.vc.len() / self.width()
+    }
+
+    fn capacity(&self) -> usize {
+        self.n
+    }
 }
 
-impl<A: UniversalAccumulator + BatchedAccumulator> DynamicVectorCommitment for VectorCommitment<A> {
-    fn update(&mut self, b: &Self::Domain, b_prime: &Self::Domain, i: usize) {
-        if b == b_prime {
-            // Nothing to do
-        } else {
-            let comm = hash_binary(b, self.lambda).into_iter();
-            let comm_prime = hash_binary(b_prime, self.lambda).into_iter();
-            let offset = i * self.lambda;
-            let is = (0..self.lambda).map(|j| offset + j);
-
-            // This is updating bit by bit, but only those bits that actually changed require work.
-            for (el, (el_prime, i)) in comm.zip(comm_prime.zip(is)) {
-                self.vc.update(&el, &el_prime, i);
+impl<A: UniversalAccumulator + BatchedAccumulator + MaybeSync> DynamicVectorCommitment
+    for VectorCommitment<A>
+{
+    fn update(
+        &mut self,
+        b: &Self::Domain,
+        b_prime: &Self::Domain,
+        i: usize,
+    ) -> Result<(), VectorCommitmentError> {
+        self.check_index(i)?;
+
+        let bits = self.update_bits(&[(i, b_prime.clone(), b.clone())])?;
+        // all old bits are checked first, such that a wrong `b_prime` changes nothing
+        self.vc.check_updates(&bits)?;
+
+        // This is updating bit by bit, but only those bits that actually changed require work.
+        for (j, el_prime, el) in bits {
+            if el != el_prime {
+                self.vc.update(&el, &el_prime, j)?;
             }
         }
+
+        Ok(())
     }
 }
 
-fn hash_binary(m: &BigUint, lambda: usize) -> BitVec<u8, Msb0> {
+/// Returns the bits of `b` at position `i`, for entries encoded with `encoding` and the security
+/// parameter `lambda`, and their indices in the binary vector.
+fn bits(
+    b: &BigUint,
+    i: usize,
+    encoding: Encoding,
+    lambda: usize,
+) -> Result<(Vec<bool>, Vec<usize>), VectorCommitmentError> {
+    let comm = value_bits(b, encoding, lambda)?;
+    let offset = i * encoding.width(lambda);
+    let is = (0..comm.len()).map(|j| offset + j).collect::<Vec<_>>();
+
+    Ok((comm, is))
+}
+
+/// Returns the bits of all `b` at positions `is`, and their indices in the binary vector.
+fn all_bits(
+    b: &[BigUint],
+    is: &[usize],
+    encoding: Encoding,
+    lambda: usize,
+) -> Result<(Vec<bool>, Vec<usize>), VectorCommitmentError> {
+    check_lengths(b, is)?;
+
+    let width = encoding.width(lambda);
+    let mut comm = Vec::with_capacity(width * b.len());
+    let mut comm_is = Vec::with_capacity(width * is.len());
+
+    for (el, i) in b.iter().zip(is) {
+        let (c, c_is) = bits(el, *i, encoding, lambda)?;
+        comm.extend(c);
+        comm_is.extend(c_is);
+    }
+
+    Ok((comm, comm_is))
+}
+
+/// Returns the products of the primes of the bits set and cleared by the update `u`.
+fn update_primes(
+    u: &VectorUpdate<BigUint>,
+    encoding: Encoding,
+    lambda: usize,
+) -> Result<(BigUint, BigUint), VectorCommitmentError> {
+    let (comm, is) = bits(&u.b, u.i, encoding, lambda)?;
+    let (comm_prime, _) = bits(&u.b_prime, u.i, encoding, lambda)?;
+
+    let mut added = Vec::new();
+    let mut deleted = Vec::new();
+    for ((el, el_prime), i) in comm.into_iter().zip(comm_prime).zip(is) {
+        if el && !el_prime {
+            added.push(index_prime(i));
+        } else if !el && el_prime {
+            deleted.push(index_prime(i));
+        }
+    }
+
+    Ok((product(&added), product(&deleted)))
+}
+
+/// Returns the bits of the entry for `m`, encoded with `encoding`.
+fn value_bits(
+    m: &BigUint,
+    encoding: Encoding,
+    lambda: usize,
+) -> Result<Vec<bool>, VectorCommitmentError> {
+    match encoding {
+        Encoding::Hashed => Ok(hash_binary(m, lambda).into_iter().collect()),
+        Encoding::Raw { width } => raw_binary(m, width),
+    }
+}
+
+/// Returns the concatenated bits of the entries for all `ms`.
+fn encode(
+    ms: &[BigUint],
+    encoding: Encoding,
+    lambda: usize,
+) -> Result<Vec<bool>, VectorCommitmentError> {
+    let width = encoding.width(lambda);
+    let mut comm = Vec::with_capacity(width * ms.len());
+    for m in ms {
+        let c = value_bits(m, encoding, lambda)?;
+        debug_assert!(c.len() == width);
+        comm.extend(c);
+    }
+
+    Ok(comm)
+}
+
+/// Returns the `width` bits of `m`, big endian, or an error if `m` does not fit.
+fn raw_binary(m: &BigUint, width: usize) -> Result<Vec<bool>, VectorCommitmentError> {
+    if m.bits() > width {
+        return Err(VectorCommitmentError::ValueTooWide {
+            bits: m.bits(),
+            width,
+        });
+    }
+
+    let bytes = m.to_bytes_le();
+    Ok((0..width)
+        .rev()
+        .map(|j| (bytes.get(j / 8).copied().unwrap_or(0) >> (j % 8)) & 1 == 1)
+        .collect())
+}
+
+pub(super) fn hash_binary(m: &BigUint, lambda: usize) -> BitVec<u8, Msb0> {
     let bytes = Blake2b512::digest(&m.to_bytes_be());
-    let len = std::cmp::min(bytes.len(), lambda / 8);
 
-    BitVec::from_slice(&bytes[..len])
+    BitVec::from_slice(&bytes[..hash_width(lambda) / 8])
+}
+
+/// Returns the number of bits of [hash_binary], for the security parameter `lambda`.
+fn hash_width(lambda: usize) -> usize {
+    8 * std::cmp::min(Blake2b512::output_size(), lambda / 8)
 }
 
 #[cfg(test)]
@@ -148,10 +573,10 @@ mod tests {
         let mut vc = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda, n);
 
         let val: Vec<BigUint> = (0..3).map(|_| rng.gen_biguint(16)).collect();
-        vc.commit(&val);
+        vc.commit(&val).unwrap();
 
         for i in 0..3 {
-            let comm = vc.open(&val[i], i);
+            let comm = vc.open(&val[i], i).unwrap();
             assert!(vc.verify(&val[i], i, &comm), "invalid commitment {}", i);
         }
     }
@@ -165,14 +590,27 @@ mod tests {
         let mut vc = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda, n);
 
         let val: Vec<BigUint> = (0..4).map(|_| rng.gen_biguint(32)).collect();
-        vc.commit(&val);
+        vc.commit(&val).unwrap();
 
         let committed = vec![val[1].clone(), val[3].clone()];
-        let comm = vc.batch_open(&committed, &[1, 3]);
+        let comm = vc.batch_open(&committed, &[1, 3]).unwrap();
         assert!(
             vc.batch_verify(&committed, &[1, 3], &comm),
             "invalid commitment"
         );
+
+        // values and positions of different lengths are not truncated to the shorter
+        assert_eq!(
+            vc.batch_open(&committed, &[1]),
+            Err(VectorCommitmentError::LengthMismatch {
+                values: 2,
+                positions: 1,
+            })
+        );
+        let comm = vc.batch_open(&committed[..1], &[1]).unwrap();
+        assert!(vc.batch_verify(&committed[..1], &[1], &comm));
+        assert!(!vc.batch_verify(&committed, &[1], &comm));
+        assert!(!vc.batch_verify(&committed[..1], &[1, 3], &comm));
     }
 
     #[test]
@@ -184,13 +622,13 @@ mod tests {
         let mut vc = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda, n);
         let val: Vec<BigUint> = (0..4).map(|_| rng.gen_biguint(32)).collect();
 
-        vc.commit(&val);
+        vc.commit(&val).unwrap();
 
-        let comm = vc.open(&val[2], 2);
+        let comm = vc.open(&val[2], 2).unwrap();
         assert!(vc.verify(&val[2], 2, &comm), "invalid commitment");
 
         let new_val = rng.gen_biguint(128);
-        vc.update(&new_val, &val[2], 2);
+        vc.update(&new_val, &val[2], 2).unwrap();
 
         // ensure old commitment fails now
         assert!(
@@ -198,7 +636,284 @@ mod tests {
             "commitment should be invalid"
         );
 
-        let comm_new = vc.open(&new_val, 2);
+        let comm_new = vc.open(&new_val, 2).unwrap();
         assert!(vc.verify(&new_val, 2, &comm_new), "invalid commitment");
     }
+
+    #[test]
+    fn test_general_vc_len() {
+        let lambda = 128;
+        let n = 4;
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda, n);
+        let val: Vec<BigUint> = (0..3).map(|_| rng.gen_biguint(32)).collect();
+        vc.commit(&val).unwrap();
+
+        assert_eq!(vc.len(), 3);
+        assert_eq!(vc.capacity(), 4);
+        assert!(vc.verify_len(3, &vc.prove_len()));
+        assert!(!vc.verify_len(4, &vc.prove_len()));
+
+        assert_eq!(
+            vc.open(&val[0], 3),
+            Err(VectorCommitmentError::IndexOutOfBounds { index: 3, len: 3 })
+        );
+        assert!(vc.commit(&val).is_err());
+        assert_eq!(vc.len(), 3);
+        vc.commit(&val[..1]).unwrap();
+        assert_eq!(vc.len(), 4);
+    }
+
+    #[test]
+    fn test_general_vc_append() {
+        let lambda = 128;
+        let n = 8;
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda, n);
+        let val: Vec<BigUint> = (0..3).map(|_| rng.gen_biguint(32)).collect();
+        vc.commit(&val).unwrap();
+
+        let old_state = vc.state().clone();
+        let ms: Vec<BigUint> = (0..2).map(|_| rng.gen_biguint(32)).collect();
+        let pi = vc.append(&ms).unwrap();
+
+        let modulus = vc.modulus().clone();
+        let encoding = vc.encoding();
+        let verify = |old_state: &BigUint, old_len, new_state: &BigUint, ms: &[BigUint], pi| {
+            VectorCommitment::<Accumulator>::verify_append(
+                &modulus, encoding, lambda, old_state, old_len, new_state, ms, pi,
+            )
+        };
+        assert!(verify(&old_state, 3, vc.state(), &ms, &pi));
+        assert!(!verify(&old_state, 3, vc.state(), &val[..2], &pi));
+        assert!(!verify(&old_state, 2, vc.state(), &ms, &pi));
+        assert!(!verify(&old_state, 3, &old_state, &ms, &pi));
+    }
+
+    #[test]
+    fn test_general_vc_update_witness() {
+        let lambda = 128;
+        let n = 8;
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda, n);
+        let val: Vec<BigUint> = (0..4).map(|_| rng.gen_biguint(32)).collect();
+        vc.commit(&val).unwrap();
+
+        let wit = vc.open_witness(&val[..2], &[0, 1]).unwrap();
+        let batch = vc.batch_open(&val[..2], &[0, 1]).unwrap();
+
+        let new_val = rng.gen_biguint(32);
+        let old_state = vc.state().clone();
+        vc.update(&new_val, &val[3], 3).unwrap();
+        let u = VectorUpdate {
+            old_state,
+            b: new_val,
+            b_prime: val[3].clone(),
+            i: 3,
+        };
+        let (n, a_new) = (vc.modulus(), vc.state());
+        let encoding = vc.encoding();
+        type Vc = VectorCommitment<Accumulator>;
+
+        let pi = vc.prove_witness(&wit, &val[..2], &[0, 1]).unwrap();
+        assert!(!vc.batch_verify(&val[..2], &[0, 1], &pi), "stale witness");
+        assert!(
+            !vc.batch_verify(&val[..2], &[0, 1], &batch),
+            "stale opening"
+        );
+
+        let wit =
+            Vc::update_witness(n, encoding, lambda, a_new, &wit, &val[..2], &[0, 1], &u).unwrap();
+        let pi = vc.prove_witness(&wit, &val[..2], &[0, 1]).unwrap();
+        assert!(vc.batch_verify(&val[..2], &[0, 1], &pi), "invalid update");
+
+        let batch =
+            Vc::update_batch_opening(n, encoding, lambda, a_new, &batch, &val[..2], &[0, 1], &u)
+                .unwrap();
+        assert!(
+            vc.batch_verify(&val[..2], &[0, 1], &batch),
+            "invalid update"
+        );
+
+        // the update changed an opened position
+        assert!(
+            Vc::update_witness(n, encoding, lambda, a_new, &wit, &val[2..], &[2, 3], &u).is_none()
+        );
+    }
+
+    #[test]
+    fn test_hashed_width() {
+        let m = BigUint::from(42u32);
+        for lambda in [0, 7, 100, 128, 512, 1024] {
+            let width = Encoding::Hashed.width(lambda);
+            assert_eq!(hash_binary(&m, lambda).len(), width);
+            assert!(width <= lambda && width <= 512);
+        }
+        assert_eq!(Encoding::Hashed.width(100), 96);
+        assert_eq!(Encoding::Hashed.width(1024), 512);
+    }
+
+    #[test]
+    fn test_general_vc_raw() {
+        let lambda = 128;
+        let n = 8;
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc = VectorCommitment::<Accumulator>::setup_with_encoding::<RSAGroup, _>(
+            rng,
+            lambda,
+            n,
+            Encoding::Raw { width: 64 },
+        );
+        // the width of the entries does not change the group
+        assert_eq!(vc.width(), 64);
+        assert_eq!(vc.modulus().bits(), lambda);
+        let val: Vec<BigUint> = [0u64, 1, 0xdead_beef, u64::MAX]
+            .iter()
+            .map(|v| BigUint::from(*v))
+            .collect();
+        vc.commit(&val).unwrap();
+
+        for (i, v) in val.iter().enumerate() {
+            let comm = vc.open(v, i).unwrap();
+            assert!(vc.verify(v, i, &comm), "invalid commitment {}", i);
+            assert!(!vc.verify(&(v ^ BigUint::from(4u32)), i, &comm));
+        }
+
+        // the bits are the value itself
+        assert_eq!(
+            raw_binary(&BigUint::from(5u32), 4).unwrap(),
+            vec![false, true, false, true]
+        );
+
+        let too_wide = BigUint::from(u64::MAX) + 1u32;
+        assert_eq!(
+            vc.commit(std::slice::from_ref(&too_wide)),
+            Err(VectorCommitmentError::ValueTooWide {
+                bits: 65,
+                width: 64
+            })
+        );
+        assert!(vc.update(&too_wide, &val[1], 1).is_err());
+        assert!(vc.open(&too_wide, 1).is_err());
+
+        vc.update(&BigUint::from(7u32), &val[1], 1).unwrap();
+        let comm = vc.open(&BigUint::from(7u32), 1).unwrap();
+        assert!(vc.verify(&BigUint::from(7u32), 1, &comm));
+        assert_eq!(vc.len(), val.len());
+
+        // bytes, wider than lambda
+        let mut vc = VectorCommitment::<Accumulator>::setup_with_encoding::<RSAGroup, _>(
+            rng,
+            lambda,
+            n,
+            Encoding::Raw { width: 256 },
+        );
+        let wide = BigUint::from_bytes_be(&[0xab; 32]);
+        vc.commit(&[wide.clone(), BigUint::from(1u32)]).unwrap();
+        assert_eq!(vc.len(), 2);
+        let comm = vc.open(&wide, 0).unwrap();
+        assert!(vc.verify(&wide, 0, &comm));
+
+        // a stale old value, which only differs in bits that do not change
+        let mut vc = VectorCommitment::<Accumulator>::setup_with_encoding::<RSAGroup, _>(
+            rng,
+            lambda,
+            n,
+            Encoding::Raw { width: 4 },
+        );
+        let (v5, v6, v7) = (
+            BigUint::from(5u32),
+            BigUint::from(6u32),
+            BigUint::from(7u32),
+        );
+        vc.commit(std::slice::from_ref(&v5)).unwrap();
+        let state = vc.state().clone();
+        assert_eq!(
+            vc.batch_update(&[(0, v7.clone(), v6.clone())]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.update(&v6, &v7, 0),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.update(&v7, &v7, 0),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(vc.state(), &state);
+
+        let updates = [(0, v5, v6.clone())];
+        let pi = vc.batch_update(&updates).unwrap();
+        assert!(vc.verify_batch_update(&state, &updates, &pi));
+        let comm = vc.open(&v6, 0).unwrap();
+        assert!(vc.verify(&v6, 0, &comm));
+    }
+
+    #[test]
+    fn test_general_vc_batch_update() {
+        let lambda = 128;
+        let n = 8;
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let mut vc = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda, n);
+        let val: Vec<BigUint> = (0..4).map(|_| rng.gen_biguint(32)).collect();
+        vc.commit(&val).unwrap();
+
+        let old_state = vc.state().clone();
+        let new_val: Vec<BigUint> = (0..2).map(|_| rng.gen_biguint(32)).collect();
+        let updates = [
+            (1, val[1].clone(), new_val[0].clone()),
+            (3, val[3].clone(), new_val[1].clone()),
+        ];
+        let pi = vc.batch_update(&updates).unwrap();
+        assert!(vc.verify_batch_update(&old_state, &updates, &pi));
+        assert!(!vc.verify_batch_update(&old_state, &updates[..1], &pi));
+
+        // same result as updating one by one
+        let mut expected = VectorCommitment::<Accumulator>::setup::<RSAGroup, _>(
+            &mut ChaChaRng::from_seed([0u8; 32]),
+            lambda,
+            n,
+        );
+        expected.commit(&val).unwrap();
+        expected.update(&new_val[0], &val[1], 1).unwrap();
+        expected.update(&new_val[1], &val[3], 3).unwrap();
+        assert_eq!(vc.state(), expected.state());
+
+        let comm = vc.open(&new_val[1], 3).unwrap();
+        assert!(vc.verify(&new_val[1], 3, &comm));
+
+        // duplicate or stale entries change nothing
+        let state = vc.state().clone();
+        let other = rng.gen_biguint(32);
+        assert_eq!(
+            vc.batch_update(&[
+                (0, val[0].clone(), other.clone()),
+                (0, val[0].clone(), other.clone())
+            ]),
+            Err(VectorCommitmentError::DuplicateIndex { index: 0 })
+        );
+        assert_eq!(
+            vc.batch_update(&[
+                (0, val[0].clone(), other.clone()),
+                (1, val[1].clone(), other.clone())
+            ]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            vc.update(&other, &val[3], 3),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(vc.state(), &state);
+
+        let updates = [(0, val[0].clone(), other.clone())];
+        let pi = vc.batch_update(&updates).unwrap();
+        assert!(vc.verify_batch_update(&state, &updates, &pi));
+        let twice = [updates[0].clone(), (0, other.clone(), other)];
+        assert!(!vc.verify_batch_update(&state, &twice, &pi));
+    }
 }
@@ -0,0 +1,347 @@
+use num_bigint::BigUint;
+use rand::{CryptoRng, Rng};
+
+use crate::error::VectorCommitmentError;
+use crate::traits::*;
+use crate::vc::binary::{check_lengths, open_bits, verify_bits};
+use crate::vc::general::hash_binary;
+use crate::vc::{key_prime, key_primes, BatchCommitment, Commitment};
+
+/// Commitment to a sparse map from byte keys to values.
+///
+/// Every key `k` owns the positions `(k, 0..=w)`, with `w` the width of
+/// [crate::vc::Encoding::Hashed], each mapped to a prime by [key_prime]. Position `(k, 0)` is set
+/// iff `k` is present, and positions `(k, 1..=w)` hold the bits of `hash_binary(value)`, as in
+/// [crate::vc::VectorCommitment]. Absent keys have no bit set, so absence is proven by a
+/// non-membership proof for `(k, 0)`.
+///
+/// Like the vector commitments, the map itself is not stored, so updates need the old values.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct MapCommitment<A: UniversalAccumulator + BatchedAccumulator> {
+    lambda: usize,
+    acc: A,
+}
+
+impl<A: UniversalAccumulator + BatchedAccumulator + MaybeSync> MapCommitment<A> {
+    pub fn setup<G, R>(rng: &mut R, lambda: usize) -> Self
+    where
+        G: PrimeGroup,
+        R: CryptoRng + Rng,
+    {
+        MapCommitment {
+            lambda,
+            acc: A::setup::<G, _>(rng, lambda),
+        }
+    }
+
+    /// Inserts `key`, which must not be present yet, with `value`.
+    pub fn insert(&mut self, key: &[u8], value: &BigUint) -> Result<(), VectorCommitmentError> {
+        let (bits, primes) = self.entry(key, value);
+        if self.acc.contains(&primes[0]) {
+            return Err(VectorCommitmentError::KeyPresent);
+        }
+
+        self.acc.batch_add(&set_primes(&bits, &primes));
+        Ok(())
+    }
+
+    /// Changes the value of the present `key` from `value_prime` to `value`.
+    ///
+    /// Returns an error, leaving the map unchanged, if `key` is absent or `value_prime` is not
+    /// its value.
+    pub fn update(
+        &mut self,
+        key: &[u8],
+        value: &BigUint,
+        value_prime: &BigUint,
+    ) -> Result<(), VectorCommitmentError> {
+        // all bits of value_prime must be in place, not only the ones that change
+        let (bits_prime, primes) = self.entry(key, value_prime);
+        self.check_entry(&bits_prime, &primes)?;
+        if value == value_prime {
+            return Ok(());
+        }
+
+        // only the bits that actually changed require work
+        let (bits, _) = self.entry(key, value);
+        let mut added = Vec::new();
+        let mut deleted = Vec::new();
+        for ((p, b), b_prime) in primes.iter().zip(&bits).zip(&bits_prime) {
+            if b != b_prime {
+                if *b {
+                    added.push(p.clone());
+                } else {
+                    deleted.push(p.clone());
+                }
+            }
+        }
+
+        self.batch_del(&deleted)?;
+        self.acc.batch_add(&added);
+
+        Ok(())
+    }
+
+    /// Removes the present `key`, with `value`.
+    ///
+    /// Returns an error, leaving the map unchanged, if `key` is absent or `value` is not its value.
+    pub fn delete(&mut self, key: &[u8], value: &BigUint) -> Result<(), VectorCommitmentError> {
+        // no bit of the key may remain set
+        let (bits, primes) = self.entry(key, value);
+        self.check_entry(&bits, &primes)?;
+
+        self.batch_del(&set_primes(&bits, &primes))
+    }
+
+    /// Opens the value of the present `key`.
+    ///
+    /// Returns an error if `key` is absent or `value` is not its value.
+    pub fn open(
+        &self,
+        key: &[u8],
+        value: &BigUint,
+    ) -> Result<BatchCommitment, VectorCommitmentError> {
+        let (bits, primes) = self.entry(key, value);
+        if !self.acc.contains(&primes[0]) {
+            return Err(VectorCommitmentError::KeyAbsent);
+        }
+
+        open_bits(&self.acc, &bits, &primes)
+    }
+
+    /// Verifies that `key` is present with `value`.
+    pub fn verify(&self, key: &[u8], value: &BigUint, pi: &BatchCommitment) -> bool {
+        let (bits, primes) = self.entry(key, value);
+        verify_bits(&self.acc, &bits, &primes, pi)
+    }
+
+    /// Opens the values of many present keys, with a single proof.
+    ///
+    /// Returns an error if a key is absent or a value is not the value of its key.
+    pub fn batch_open<K: AsRef<[u8]>>(
+        &self,
+        keys: &[K],
+        values: &[BigUint],
+    ) -> Result<BatchCommitment, VectorCommitmentError> {
+        check_lengths(keys, values)?;
+        if keys
+            .iter()
+            .any(|key| !self.acc.contains(&key_prime(key.as_ref(), 0)))
+        {
+            return Err(VectorCommitmentError::KeyAbsent);
+        }
+
+        let (bits, primes) = self.entries(keys, values);
+        open_bits(&self.acc, &bits, &primes)
+    }
+
+    /// Verifies that all `keys` are present with their `values`.
+    pub fn batch_verify<K: AsRef<[u8]>>(
+        &self,
+        keys: &[K],
+        values: &[BigUint],
+        pi: &BatchCommitment,
+    ) -> bool {
+        if keys.len() != values.len() {
+            return false;
+        }
+
+        let (bits, primes) = self.entries(keys, values);
+        verify_bits(&self.acc, &bits, &primes, pi)
+    }
+
+    /// Proves that `key` is absent. Fails with [VectorCommitmentError::KeyPresent] otherwise.
+    pub fn prove_absent(&self, key: &[u8]) -> Result<Commitment, VectorCommitmentError> {
+        self.acc
+            .non_mem_wit_create(&key_prime(key, 0))
+            .map(Commitment::NonMem)
+            .ok_or(VectorCommitmentError::KeyPresent)
+    }
+
+    /// Verifies that `key` is absent.
+    pub fn verify_absent(&self, key: &[u8], pi: &Commitment) -> bool {
+        match pi {
+            Commitment::Mem(_) => false,
+            Commitment::NonMem(w) => self.acc.ver_non_mem(w, &key_prime(key, 0)),
+        }
+    }
+
+    pub fn state(&self) -> &BigUint {
+        self.acc.state()
+    }
+
+    /// Deletes the set bits `primes` with a single [BatchedAccumulator::batch_del].
+    fn batch_del(&mut self, primes: &[BigUint]) -> Result<(), VectorCommitmentError> {
+        if primes.is_empty() {
+            return Ok(());
+        }
+
+        let ws = self
+            .acc
+            .mem_wit_create_batch(primes)
+            .ok_or(VectorCommitmentError::ValueMismatch)?;
+        let pairs = primes.iter().cloned().zip(ws).collect::<Vec<_>>();
+        self.acc.batch_del(&pairs).expect("valid witnesses");
+
+        Ok(())
+    }
+
+    /// Checks that the key of the entry `(bits, primes)` is present, and holds exactly `bits`.
+    fn check_entry(&self, bits: &[bool], primes: &[BigUint]) -> Result<(), VectorCommitmentError> {
+        if !self.acc.contains(&primes[0]) {
+            return Err(VectorCommitmentError::KeyAbsent);
+        }
+        if primes
+            .iter()
+            .zip(bits)
+            .any(|(p, b)| self.acc.contains(p) != *b)
+        {
+            return Err(VectorCommitmentError::ValueMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the bits of the entry `(key, value)`, including the presence bit, and their primes.
+    fn entry(&self, key: &[u8], value: &BigUint) -> (Vec<bool>, Vec<BigUint>) {
+        let mut bits = vec![true];
+        bits.extend(hash_binary(value, self.lambda).iter().map(|b| *b));
+
+        let js = (0..bits.len()).collect::<Vec<_>>();
+        let primes = key_primes(key, &js);
+
+        (bits, primes)
+    }
+
+    /// Same as [MapCommitment::entry], for many entries.
+    fn entries<K: AsRef<[u8]>>(&self, keys: &[K], values: &[BigUint]) -> (Vec<bool>, Vec<BigUint>) {
+        let mut bits = Vec::new();
+        let mut primes = Vec::new();
+        for (key, value) in keys.iter().zip(values) {
+            let (b, p) = self.entry(key.as_ref(), value);
+            bits.extend(b);
+            primes.extend(p);
+        }
+
+        (bits, primes)
+    }
+}
+
+/// Returns the primes of the set bits.
+fn set_primes(bits: &[bool], primes: &[BigUint]) -> Vec<BigUint> {
+    primes
+        .iter()
+        .zip(bits)
+        .filter(|(_, b)| **b)
+        .map(|(p, _)| p.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accumulator::Accumulator;
+    use crate::group::RSAGroup;
+    use num_bigint::RandBigInt;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_map_commitment() {
+        let lambda = 128;
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+
+        let mut mc = MapCommitment::<Accumulator>::setup::<RSAGroup, _>(rng, lambda);
+
+        let keys: Vec<Vec<u8>> = vec![b"alice".to_vec(), b"bob".to_vec(), b"carol".to_vec()];
+        let values: Vec<BigUint> = (0..3).map(|_| rng.gen_biguint(64)).collect();
+        for (key, value) in keys.iter().zip(&values) {
+            mc.insert(key, value).unwrap();
+        }
+        assert_eq!(
+            mc.insert(&keys[0], &values[1]),
+            Err(VectorCommitmentError::KeyPresent)
+        );
+
+        for (key, value) in keys.iter().zip(&values) {
+            let pi = mc.open(key, value).unwrap();
+            assert!(mc.verify(key, value, &pi), "invalid opening");
+            assert!(!mc.verify(key, &(value + 1u32), &pi), "wrong value");
+        }
+
+        let pi = mc.batch_open(&keys[..2], &values[..2]).unwrap();
+        assert!(mc.batch_verify(&keys[..2], &values[..2], &pi));
+        assert!(!mc.batch_verify(&keys[1..], &values[1..], &pi));
+
+        // absent keys
+        let pi = mc.prove_absent(b"dave").unwrap();
+        assert!(mc.verify_absent(b"dave", &pi));
+        assert!(!mc.verify_absent(b"alice", &pi));
+        assert_eq!(
+            mc.prove_absent(b"alice"),
+            Err(VectorCommitmentError::KeyPresent)
+        );
+
+        // update
+        let pi_old = mc.open(&keys[1], &values[1]).unwrap();
+        let new_value = rng.gen_biguint(64);
+        let state = mc.state().clone();
+        assert_eq!(
+            mc.update(b"dave", &new_value, &values[1]),
+            Err(VectorCommitmentError::KeyAbsent)
+        );
+        assert_eq!(
+            mc.update(&keys[1], &new_value, &values[0]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        // a stale old value is rejected, even if no bit would change
+        assert_eq!(
+            mc.update(&keys[2], &values[0], &values[0]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            mc.update(&keys[2], &values[2], &values[0]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(mc.state(), &state);
+        mc.update(&keys[2], &values[2], &values[2]).unwrap();
+        mc.update(&keys[1], &new_value, &values[1]).unwrap();
+        assert!(!mc.verify(&keys[1], &values[1], &pi_old));
+        let pi = mc.open(&keys[1], &new_value).unwrap();
+        assert!(mc.verify(&keys[1], &new_value, &pi));
+
+        // delete
+        assert_eq!(
+            mc.delete(&keys[0], &values[2]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        mc.delete(&keys[0], &values[0]).unwrap();
+        assert_eq!(
+            mc.delete(&keys[0], &values[0]),
+            Err(VectorCommitmentError::KeyAbsent)
+        );
+        let pi = mc.prove_absent(&keys[0]).unwrap();
+        assert!(mc.verify_absent(&keys[0], &pi));
+        let pi = mc.open(&keys[2], &values[2]).unwrap();
+        assert!(mc.verify(&keys[2], &values[2], &pi));
+
+        // openings of absent keys or wrong values
+        assert_eq!(
+            mc.open(&keys[0], &values[0]),
+            Err(VectorCommitmentError::KeyAbsent)
+        );
+        assert_eq!(
+            mc.open(&keys[2], &values[0]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+        assert_eq!(
+            mc.batch_open(&keys[..2], &[values[0].clone(), new_value.clone()]),
+            Err(VectorCommitmentError::KeyAbsent)
+        );
+        assert_eq!(
+            mc.batch_open(&keys[1..], &[new_value, values[0].clone()]),
+            Err(VectorCommitmentError::ValueMismatch)
+        );
+    }
+}
@@ -1,5 +1,9 @@
 mod binary;
 mod general;
+mod map;
+mod primes;
 
 pub use crate::vc::binary::*;
 pub use crate::vc::general::*;
+pub use crate::vc::map::*;
+pub use crate::vc::primes::*;
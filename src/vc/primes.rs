@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::sync::RwLock;
+
+use blake2::{Blake2b512, Digest};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use num_bigint::prime::{next_prime, probably_prime};
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::parallel;
+
+/// Index primes that were already computed, shared by all vector commitments.
+static INDEX_PRIMES: RwLock<Option<BTreeMap<usize, BigUint>>> = RwLock::new(None);
+
+/// Returns the start of the range of the prime for index `i`, `2^128 + i 2^64`.
+///
+/// The ranges of different indices are disjoint, and above all outputs of [crate::hash::hash_prime],
+/// so the mapping is injective, and never collides with other accumulated primes.
+fn index_base(i: usize) -> BigUint {
+    (BigUint::one() << 128) + (BigUint::from(i as u64) << 64)
+}
+
+/// Returns the prime for index `i`, the smallest prime in `(2^128 + i 2^64, 2^128 + (i + 1) 2^64)`.
+fn compute_index_prime(i: usize) -> BigUint {
+    let p = next_prime(&index_base(i));
+    debug_assert!(p < index_base(i + 1), "no prime in range");
+
+    p
+}
+
+/// Returns true if `p` is the prime of index `i`, see [compute_index_prime].
+///
+/// All numbers are checked with the Baillie-PSW test only, which takes about half the time of
+/// computing the prime.
+fn is_index_prime(i: usize, p: &BigUint) -> bool {
+    let base = index_base(i);
+    if p <= &base || p >= &(&base + (BigUint::one() << 64)) || !probably_prime(p, 0) {
+        return false;
+    }
+
+    // the base is even, so only the odd numbers in between need to be composite
+    let mut c = base + 1u32;
+    while &c < p {
+        if probably_prime(&c, 0) {
+            return false;
+        }
+        c += 2u32;
+    }
+
+    true
+}
+
+/// Width of the range of the prime of a position of a map key.
+const KEY_RANGE_BITS: usize = 16;
+
+/// Returns the start of the range of the prime for position `j` of `key`, `2^160 + H(key) 2^32 +
+/// j 2^16`, for the first 128 bits `H(key)` of its Blake2b hash.
+///
+/// The ranges are disjoint for `j < 2^16` and keys with distinct hashes, and above the ranges of
+/// all indices.
+fn key_base(key: &[u8], j: usize) -> BigUint {
+    assert!(j < 1 << KEY_RANGE_BITS, "position out of range");
+
+    let h = BigUint::from_bytes_be(&Blake2b512::digest(key)[..16]);
+    (BigUint::one() << 160) + (h << (2 * KEY_RANGE_BITS)) + (BigUint::from(j) << KEY_RANGE_BITS)
+}
+
+/// Maps position `j` of `key` in a map commitment to its prime, the smallest prime in its range,
+/// see [key_base]. Like indices, distinct positions never share a prime, except for a collision
+/// of the hashes of their keys. Keys are unbounded, so their primes are not cached.
+pub fn key_prime(key: &[u8], j: usize) -> BigUint {
+    let base = key_base(key, j);
+    let p = next_prime(&base);
+    debug_assert!(p < base + (1u32 << KEY_RANGE_BITS), "no prime in range");
+
+    p
+}
+
+/// Maps all positions `js` of `key` to their primes, see [key_prime].
+pub fn key_primes(key: &[u8], js: &[usize]) -> Vec<BigUint> {
+    parallel::map(js, |j| key_prime(key, *j))
+}
+
+/// Maps the index `i` to its prime, using the cache of already computed primes.
+pub fn index_prime(i: usize) -> BigUint {
+    index_primes(&[i]).pop().expect("one prime")
+}
+
+/// Maps all indices `is` to their primes, computing only those that are not cached yet.
+pub fn index_primes(is: &[usize]) -> Vec<BigUint> {
+    let mut missing = {
+        let cache = INDEX_PRIMES.read().expect("poisoned lock");
+        match cache.as_ref() {
+            Some(cache) => {
+                let cached = is
+                    .iter()
+                    .map(|i| cache.get(i).cloned())
+                    .collect::<Option<Vec<_>>>();
+                if let Some(cached) = cached {
+                    return cached;
+                }
+                is.iter()
+                    .filter(|i| !cache.contains_key(i))
+                    .copied()
+                    .collect::<Vec<_>>()
+            }
+            None => is.to_vec(),
+        }
+    };
+    missing.sort_unstable();
+    missing.dedup();
+
+    let primes = parallel::map(&missing, |i| compute_index_prime(*i));
+
+    let mut cache = INDEX_PRIMES.write().expect("poisoned lock");
+    let cache = cache.get_or_insert_with(BTreeMap::new);
+    cache.extend(missing.into_iter().zip(primes));
+
+    is.iter().map(|i| cache[i].clone()).collect()
+}
+
+/// Empties the cache of index primes.
+pub fn clear_index_primes() {
+    *INDEX_PRIMES.write().expect("poisoned lock") = None;
+}
+
+/// Writes all cached index primes to `w`, such that they can be restored with
+/// [load_index_primes]. Each prime is stored as its index and its offset in its range.
+pub fn save_index_primes<W: Write>(w: W) -> io::Result<()> {
+    let cache = INDEX_PRIMES.read().expect("poisoned lock");
+    let empty = BTreeMap::new();
+
+    write_index_primes(cache.as_ref().unwrap_or(&empty), w)
+}
+
+/// Writes the index primes `primes` in the format of [save_index_primes].
+fn write_index_primes<W: Write>(primes: &BTreeMap<usize, BigUint>, mut w: W) -> io::Result<()> {
+    w.write_u64::<BigEndian>(primes.len() as u64)?;
+    for (i, p) in primes {
+        let offset = p - index_base(*i);
+        w.write_u64::<BigEndian>(*i as u64)?;
+        w.write_u64::<BigEndian>(offset.get_limb(0))?;
+    }
+
+    Ok(())
+}
+
+/// Reads index primes written by [save_index_primes] from `r` into the cache, returning their
+/// number. Every prime is checked to be the smallest prime in the range of its index, so a table
+/// from an untrusted source cannot change the mapping, and nothing is cached if one is not.
+pub fn load_index_primes<R: Read>(r: R) -> io::Result<usize> {
+    let entries = read_index_primes(r)?;
+    let len = entries.len();
+
+    let mut cache = INDEX_PRIMES.write().expect("poisoned lock");
+    cache.get_or_insert_with(BTreeMap::new).extend(entries);
+
+    Ok(len)
+}
+
+/// Reads and checks the index primes written by [save_index_primes].
+fn read_index_primes<R: Read>(mut r: R) -> io::Result<Vec<(usize, BigUint)>> {
+    let len = r.read_u64::<BigEndian>()?;
+
+    let mut entries = Vec::new();
+    for _ in 0..len {
+        let i = r.read_u64::<BigEndian>()? as usize;
+        let offset = r.read_u64::<BigEndian>()?;
+        entries.push((i, index_base(i) + offset));
+    }
+
+    let valid = parallel::map(&entries, |(i, p)| is_index_prime(*i, p));
+    if valid.iter().any(|valid| !valid) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an index prime",
+        ));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_primes() {
+        clear_index_primes();
+
+        let is = [0, 1, 2, 7, 1000, 1];
+        let ps = index_primes(&is);
+
+        for (i, p) in is.iter().zip(&ps) {
+            assert!(probably_prime(p, 20));
+            assert!(p > &index_base(*i) && p < &index_base(*i + 1));
+            assert_eq!(p, &compute_index_prime(*i));
+            assert_eq!(p, &index_prime(*i));
+            assert!(is_index_prime(*i, p));
+        }
+        assert_eq!(ps[1], ps[5]);
+        assert_ne!(ps[0], ps[1]);
+
+        // 2^128 + 1 is a base 2 pseudoprime, but not a prime
+        assert!(!probably_prime(&(index_base(0) + 1u32), 0));
+        assert!(!is_index_prime(0, &next_prime(&ps[0])));
+        assert!(!is_index_prime(1, &ps[0]));
+    }
+
+    #[test]
+    fn test_key_primes() {
+        let ps = key_primes(b"key", &[0, 1, 2]);
+
+        for (j, p) in ps.iter().enumerate() {
+            assert!(probably_prime(p, 20));
+            assert!(p > &key_base(b"key", j) && p < &key_base(b"key", j + 1));
+            assert_eq!(p, &key_prime(b"key", j));
+            assert!(p > &index_base(usize::MAX));
+        }
+        assert_ne!(ps[0], ps[1]);
+        assert_ne!(key_prime(b"key", 0), key_prime(b"other key", 0));
+    }
+
+    #[test]
+    fn test_index_primes_persistence() {
+        let is = [3, 4, 5];
+        let ps = index_primes(&is);
+        let primes = is
+            .iter()
+            .copied()
+            .zip(ps.clone())
+            .collect::<BTreeMap<_, _>>();
+
+        let mut table = Vec::new();
+        write_index_primes(&primes, &mut table).unwrap();
+        assert_eq!(
+            read_index_primes(&table[..]).unwrap(),
+            primes.into_iter().collect::<Vec<_>>()
+        );
+
+        clear_index_primes();
+        assert_eq!(load_index_primes(&table[..]).unwrap(), 3);
+        assert_eq!(index_primes(&is), ps);
+
+        // the whole cache, including the primes of other tests running in parallel
+        let mut saved = Vec::new();
+        save_index_primes(&mut saved).unwrap();
+        let entries = read_index_primes(&saved[..]).unwrap();
+        assert!(entries.contains(&(3, ps[0].clone())));
+        assert_eq!(load_index_primes(&saved[..]).unwrap(), entries.len());
+
+        let entry = |i: u64, offset: u64| {
+            let mut bad = Vec::new();
+            bad.write_u64::<BigEndian>(1).unwrap();
+            bad.write_u64::<BigEndian>(i).unwrap();
+            bad.write_u64::<BigEndian>(offset).unwrap();
+            bad
+        };
+        let invalid = |table: &[u8]| load_index_primes(table).unwrap_err().kind();
+
+        // an offset that is not a prime
+        assert_eq!(invalid(&entry(3, 0)), io::ErrorKind::InvalidData);
+
+        // a prime, but not the smallest one in the range
+        let next = next_prime(&ps[0]) - index_base(3);
+        assert_eq!(
+            invalid(&entry(3, next.get_limb(0))),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(index_prime(3), ps[0]);
+
+        // truncated
+        assert!(load_index_primes(&table[..table.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! Verifiable delay functions, evaluating `x^(2^T)` in a group of unknown order.
+//!
+//! All operations are in a group of unknown order, either `Z_n^*` for an RSA modulus `n`, generated
+//! by one of the backends in [crate::group], or the class group of a [crate::group::Discriminant].
+//! Two proof systems are available:
+//!
+//! - Wesolowski, [prove] and [verify]: a single group element, but proving and verifying need a
+//!   hash to prime.
+//! - [pietrzak]: `log(T)` group elements, using only short challenges, and a cheaper prover.
+
+use crate::traits::UnknownOrderGroup;
+
+pub mod pietrzak;
+mod wesolowski;
+
+pub use self::wesolowski::*;
+
+/// Evaluates the VDF, `y = x^(2^t)`, by `t` sequential squarings in `group`.
+pub fn eval<G: UnknownOrderGroup>(x: &G::Elem, t: u64, group: &G) -> G::Elem {
+    group.repeated_square(x, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::{BigUint, RandBigInt, RandPrime};
+    use num_traits::One;
+
+    use crate::group::Discriminant;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_eval() {
+        let mut rng = thread_rng();
+
+        let n = rng.gen_prime(128) * rng.gen_prime(128);
+        let x = rng.gen_biguint(256) % &n;
+
+        for t in 0..64 {
+            let e = BigUint::one() << t as usize;
+            assert_eq!(eval(&x, t, &n), x.modpow(&e, &n));
+        }
+
+        let d = Discriminant::from_seed(b"eval", 256);
+        let g = d.generator();
+        for t in 0..16 {
+            let e = BigUint::one() << t as usize;
+            assert_eq!(eval(&g, t, &d), d.pow(&g, &e));
+        }
+    }
+}
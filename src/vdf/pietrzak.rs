@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+
+use crate::proofs::Transcript;
+use crate::traits::UnknownOrderGroup;
+
+// Pietrzak's halving protocol: the claim `x^(2^t) = y` is reduced to the claim
+// `x'^(2^(t/2)) = y'`, with `mu = x^(2^(t/2))`, `x' = x^r mu` and `y' = mu^r y`,
+// for a short challenge `r`, until `t = 1`.
+//
+// After `i` rounds `x_i = x^(prod_k (r_k + 2^(h_k)))`, for the halvings `h_k`, so the
+// midpoint `mu_i = x_i^(2^(h_i))` is a product of powers `x^(2^(h_i + sum_{k in S} h_k))`,
+// over all subsets `S` of the previous rounds. These powers are saved while evaluating,
+// such that the first rounds, which would otherwise need most of the squarings, are cheap.
+//
+// In `Z_n^*` the element `-1` would let the prover answer `-y`, e.g. as the squaring
+// of odd `t` maps both to `y^2`. So the output and all midpoints are normalized by
+// `UnknownOrderGroup::normalize`, which maps them to `QR_n^+ = Z_n^* / {±1}`, and the verifier
+// only accepts normalized elements.
+
+/// Size of the challenges, in bytes.
+const CHALLENGE_BYTES: usize = 16;
+
+/// The intermediate values `mu` for each halving round.
+pub type PietrzakProof<E> = Vec<E>;
+
+/// The powers `x^(2^j)` saved by [eval], from which [prove] computes the midpoints
+/// of its first rounds.
+#[derive(Debug, Clone)]
+pub struct Checkpoints<E> {
+    powers: BTreeMap<u64, E>,
+}
+
+/// Returns the halving `h_i` of each round, for a claim about `x^(2^t)`.
+fn halvings(mut t: u64) -> Vec<u64> {
+    let mut hs = Vec::new();
+    while t > 1 {
+        t = Integer::div_ceil(&t, &2);
+        hs.push(t);
+    }
+
+    hs
+}
+
+/// Returns the number of rounds, whose midpoints are computed from checkpoints.
+///
+/// Round `i` combines `2^i` checkpoints with exponents of about `8 * CHALLENGE_BYTES * i` bits,
+/// while squaring costs `h_i ~ t / 2^(i + 1)`, so checkpoints are used while `2^(2i + 9) < t`.
+fn checkpoint_rounds(hs: &[u64], t: u64) -> usize {
+    (0..hs.len())
+        .take_while(|i| 2 * i + 9 < 64 && 1u64 << (2 * i + 9) < t)
+        .count()
+}
+
+/// Returns the exponents `j` of the checkpoints `x^(2^j)` needed for the first `rounds` rounds.
+fn checkpoint_positions(hs: &[u64], rounds: usize) -> BTreeSet<u64> {
+    let mut positions = BTreeSet::new();
+    for (i, h) in hs.iter().enumerate().take(rounds) {
+        for mask in 0..1usize << i {
+            positions.insert(h + subset_sum(hs, mask));
+        }
+    }
+
+    positions
+}
+
+/// Returns `sum_{k in mask} h_k`.
+fn subset_sum(hs: &[u64], mask: usize) -> u64 {
+    hs.iter()
+        .enumerate()
+        .filter(|(k, _)| (mask >> k) & 1 == 1)
+        .map(|(_, h)| h)
+        .sum()
+}
+
+/// Evaluates the VDF, `y = x^(2^t)` in `group`, like [crate::vdf::eval], but normalized by
+/// [UnknownOrderGroup::normalize], and saves the checkpoints needed by [prove].
+pub fn eval<G: UnknownOrderGroup>(
+    x: &G::Elem,
+    t: u64,
+    group: &G,
+) -> (G::Elem, Checkpoints<G::Elem>) {
+    let hs = halvings(t);
+    let mut positions = checkpoint_positions(&hs, checkpoint_rounds(&hs, t));
+    positions.insert(t);
+
+    // the positions may exceed t by a few squarings, due to rounding up odd halvings
+    let mut powers = BTreeMap::new();
+    let mut cur = x.clone();
+    let mut j = 0;
+    for pos in positions {
+        cur = group.repeated_square(&cur, pos - j);
+        j = pos;
+        powers.insert(pos, cur.clone());
+    }
+
+    let y = group.normalize(&powers[&t]);
+    (y, Checkpoints { powers })
+}
+
+/// Proves that `y = x^(2^t)` in `group`, using the checkpoints created by [eval] for `x` and `t`.
+///
+/// Panics if checkpoints for another `t` are given.
+pub fn prove<G: UnknownOrderGroup>(
+    x: &G::Elem,
+    y: &G::Elem,
+    t: u64,
+    checkpoints: &Checkpoints<G::Elem>,
+    group: &G,
+) -> PietrzakProof<G::Elem> {
+    debug_assert!(
+        &group.normalize(&crate::vdf::eval(x, t, group)) == y,
+        "invalid input"
+    );
+
+    let hs = halvings(t);
+    let rounds = checkpoint_rounds(&hs, t);
+
+    let mut x = x.clone();
+    let mut y = y.clone();
+    let mut t = t;
+    let mut rs = Vec::new();
+    let mut pi = Vec::new();
+
+    for (i, &h) in hs.iter().enumerate() {
+        if t % 2 == 1 {
+            // x^(2^(t + 1)) = y^2
+            y = group.square(&y);
+        }
+        t = h;
+
+        // mu <- x^(2^(t/2)), the sign of x is lost by squaring for t > 0
+        let mu = group.normalize(&if i < rounds {
+            midpoint(&hs, i, &rs, checkpoints, group)
+        } else {
+            group.repeated_square(&x, t)
+        });
+        let r = challenge(&x, &y, &mu, t, group);
+
+        // x <- x^r mu, y <- mu^r y
+        x = group.op(&group.pow(&x, &r), &mu);
+        y = group.op(&group.pow(&mu, &r), &y);
+
+        rs.push(r);
+        pi.push(mu);
+    }
+
+    pi
+}
+
+/// Computes `mu_i = prod_S x^(2^(h_i + sum_{k in S} h_k) prod_{k not in S} r_k)` from the
+/// checkpoints, folding over the previous rounds `k`, such that each needs one
+/// exponentiation by `r_k` per remaining subset.
+fn midpoint<G: UnknownOrderGroup>(
+    hs: &[u64],
+    i: usize,
+    rs: &[BigUint],
+    checkpoints: &Checkpoints<G::Elem>,
+    group: &G,
+) -> G::Elem {
+    let mut terms = (0..1usize << i)
+        .map(|mask| {
+            let pos = hs[i] + subset_sum(hs, mask);
+            match checkpoints.powers.get(&pos) {
+                Some(power) => power.clone(),
+                None => panic!("missing checkpoint x^(2^{})", pos),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for k in (0..i).rev() {
+        let half = 1 << k;
+        terms = (0..half)
+            .map(|mask| group.op(&terms[mask | half], &group.pow(&terms[mask], &rs[k])))
+            .collect();
+    }
+
+    terms.pop().expect("one term")
+}
+
+/// Verifies that `y = x^(2^t)` in `group`, up to [UnknownOrderGroup::normalize].
+/// Rejects `y` or intermediate values, which are not normalized.
+pub fn verify<G: UnknownOrderGroup>(
+    x: &G::Elem,
+    y: &G::Elem,
+    t: u64,
+    pi: &[G::Elem],
+    group: &G,
+) -> bool {
+    if &group.normalize(y) != y || pi.iter().any(|mu| &group.normalize(mu) != mu) {
+        return false;
+    }
+
+    let mut x = x.clone();
+    let mut y = y.clone();
+    let mut t = t;
+    let mut mus = pi.iter();
+
+    while t > 1 {
+        if t % 2 == 1 {
+            y = group.square(&y);
+            t += 1;
+        }
+        t /= 2;
+
+        let mu = match mus.next() {
+            Some(mu) => mu,
+            None => return false,
+        };
+        let r = challenge(&x, &y, mu, t, group);
+
+        x = group.op(&group.pow(&x, &r), mu);
+        y = group.op(&group.pow(mu, &r), &y);
+    }
+
+    // all intermediate values must be used
+    if mus.next().is_some() {
+        return false;
+    }
+
+    group.normalize(&group.repeated_square(&x, t)) == group.normalize(&y)
+}
+
+/// r <- H(x, y, mu, t)
+fn challenge<G: UnknownOrderGroup>(
+    x: &G::Elem,
+    y: &G::Elem,
+    mu: &G::Elem,
+    t: u64,
+    group: &G,
+) -> BigUint {
+    let mut transcript = Transcript::new();
+    group.transcribe(x, &mut transcript);
+    group.transcribe(y, &mut transcript);
+    group.transcribe(mu, &mut transcript);
+    transcript
+        .append(&BigUint::from(t))
+        .challenge_bytes(CHALLENGE_BYTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::{RandBigInt, RandPrime};
+    use rand::thread_rng;
+
+    use crate::group::Discriminant;
+
+    #[test]
+    fn test_pietrzak() {
+        let mut rng = thread_rng();
+
+        for i in 1..4 {
+            let n = rng.gen_prime(i * 64) * rng.gen_prime(i * 64);
+
+            for &t in &[0, 1, 2, 3, 127, 128, 129, 1000, 5000] {
+                let x = rng.gen_biguint(i * 128) % &n;
+                let (y, checkpoints) = eval(&x, t, &n);
+                assert_eq!(y, n.normalize(&crate::vdf::eval(&x, t, &n)));
+
+                let pi = prove(&x, &y, t, &checkpoints, &n);
+                assert!(verify(&x, &y, t, &pi, &n), "invalid proof for t = {}", t);
+
+                assert!(!verify(&x, &y, t + 1, &pi, &n), "verified wrong t");
+
+                let y_bad = (&y * 2u32) % &n;
+                assert!(!verify(&x, &y_bad, t, &pi, &n), "verified wrong y");
+
+                // -y has the same square, but is not normalized
+                let y_neg = &n - &y;
+                assert!(!verify(&x, &y_neg, t, &pi, &n), "verified -y");
+                assert!(!verify(&x, &(&y + &n), t, &pi, &n), "verified unreduced y");
+
+                if let Some(mu) = pi.first() {
+                    let mut pi_bad = pi.clone();
+                    pi_bad[0] = (mu * 2u32) % &n;
+                    assert!(!verify(&x, &y, t, &pi_bad, &n), "verified wrong mu");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pietrzak_checkpoints() {
+        let mut rng = thread_rng();
+        let n = rng.gen_prime(64) * rng.gen_prime(64);
+        let x = rng.gen_biguint(128) % &n;
+
+        for &t in &[1000, 1001, 1 << 12, (1 << 14) + 3] {
+            let hs = halvings(t);
+            let rounds = checkpoint_rounds(&hs, t);
+            assert!(rounds > 0 && rounds < hs.len());
+
+            // the checkpoints give the same proof as squaring in every round
+            let (y, checkpoints) = eval(&x, t, &n);
+            let pi = prove(&x, &y, t, &checkpoints, &n);
+            assert!(checkpoints.powers.len() <= 1 << rounds);
+
+            let mut cur = x.clone();
+            let mut y_cur = y.clone();
+            let mut t_cur = t;
+            for (mu, h) in pi.iter().zip(&hs) {
+                if t_cur % 2 == 1 {
+                    y_cur = (&y_cur * &y_cur) % &n;
+                }
+                t_cur = *h;
+                assert_eq!(mu, &n.normalize(&crate::vdf::eval(&cur, t_cur, &n)));
+
+                let r = challenge(&cur, &y_cur, mu, t_cur, &n);
+                cur = (cur.modpow(&r, &n) * mu) % &n;
+                y_cur = (mu.modpow(&r, &n) * &y_cur) % &n;
+            }
+        }
+    }
+
+    #[test]
+    fn test_pietrzak_class_group() {
+        let d = Discriminant::from_seed(b"pietrzak", 256);
+        let x = d.generator();
+
+        for &t in &[0, 1, 2, 3, 100, 1000] {
+            let (y, checkpoints) = eval(&x, t, &d);
+
+            let pi = prove(&x, &y, t, &checkpoints, &d);
+            assert!(verify(&x, &y, t, &pi, &d), "invalid proof for t = {}", t);
+
+            assert!(!verify(&x, &y, t + 1, &pi, &d), "verified wrong t");
+
+            let y_bad = d.square(&y);
+            assert!(!verify(&x, &y_bad, t, &pi, &d), "verified wrong y");
+        }
+    }
+}
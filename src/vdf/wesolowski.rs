@@ -0,0 +1,127 @@
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::proofs::Transcript;
+use crate::traits::UnknownOrderGroup;
+use crate::vdf::eval;
+
+// The Wesolowski proof is the NI-PoE for `x^(2^T) = y`. As `2^T` can not be
+// materialized for large `T`, the quotient `floor(2^T / l)` is computed bit by bit,
+// while squaring.
+
+/// Number of quotient bits, that are multiplied in at once from a table of powers of `x`.
+const WINDOW: u64 = 8;
+
+/// Proves that `y = x^(2^t)` in `group`.
+pub fn prove<G: UnknownOrderGroup>(x: &G::Elem, y: &G::Elem, t: u64, group: &G) -> G::Elem {
+    debug_assert!(&eval(x, t, group) == y, "invalid input");
+
+    // l <- H_prime(x, y, t)
+    let l = challenge(x, y, t, group);
+
+    // x^j, for all windows
+    let mut table = vec![group.identity()];
+    for j in 1..1usize << std::cmp::min(WINDOW, t) {
+        let next = group.op(&table[j - 1], x);
+        table.push(next);
+    }
+
+    // pi <- x^floor(2^t / l), using long division
+    let mut pi = group.identity();
+    let mut r = BigUint::one();
+    let mut done = 0;
+    while done < t {
+        let k = std::cmp::min(WINDOW, t - done);
+
+        // the next k bits of the quotient
+        let mut window = 0;
+        for _ in 0..k {
+            r <<= 1;
+            window <<= 1;
+            if r >= l {
+                r -= &l;
+                window |= 1;
+            }
+        }
+
+        pi = group.op(&group.repeated_square(&pi, k), &table[window]);
+        done += k;
+    }
+
+    pi
+}
+
+/// Verifies that `y = x^(2^t)` in `group`.
+pub fn verify<G: UnknownOrderGroup>(
+    x: &G::Elem,
+    y: &G::Elem,
+    t: u64,
+    pi: &G::Elem,
+    group: &G,
+) -> bool {
+    // l <- H_prime(x, y, t)
+    let l = challenge(x, y, t, group);
+
+    // r <- 2^t mod l
+    let r = BigUint::from(2u32).modpow(&BigUint::from(t), &l);
+
+    // pi^l x^r == y
+    &group.multi_pow(&[(pi, &l), (x, &r)]) == y
+}
+
+fn challenge<G: UnknownOrderGroup>(x: &G::Elem, y: &G::Elem, t: u64, group: &G) -> BigUint {
+    let mut transcript = Transcript::new();
+    group.transcribe(x, &mut transcript);
+    group.transcribe(y, &mut transcript);
+    transcript.append(&BigUint::from(t)).challenge_prime()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_bigint::{RandBigInt, RandPrime};
+    use rand::thread_rng;
+
+    use crate::group::Discriminant;
+
+    #[test]
+    fn test_wesolowski() {
+        let mut rng = thread_rng();
+
+        for i in 1..4 {
+            let n = rng.gen_prime(i * 64) * rng.gen_prime(i * 64);
+
+            for &t in &[0, 1, 2, 127, 128, 129, 1000] {
+                let x = rng.gen_biguint(i * 128) % &n;
+                let y = eval(&x, t, &n);
+
+                let pi = prove(&x, &y, t, &n);
+                assert!(verify(&x, &y, t, &pi, &n), "invalid proof for t = {}", t);
+
+                assert!(!verify(&x, &y, t + 1, &pi, &n), "verified wrong t");
+
+                let y_bad = (&y * 2u32) % &n;
+                assert!(!verify(&x, &y_bad, t, &pi, &n), "verified wrong y");
+            }
+        }
+    }
+
+    #[test]
+    fn test_wesolowski_class_group() {
+        let d = Discriminant::from_seed(b"wesolowski", 256);
+        let x = d.generator();
+
+        for &t in &[0, 1, 2, 7, 8, 9, 100] {
+            let y = eval(&x, t, &d);
+
+            let pi = prove(&x, &y, t, &d);
+            assert!(verify(&x, &y, t, &pi, &d), "invalid proof for t = {}", t);
+
+            assert!(!verify(&x, &y, t + 1, &pi, &d), "verified wrong t");
+
+            let y_bad = d.square(&y);
+            assert!(!verify(&x, &y_bad, t, &pi, &d), "verified wrong y");
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Membership witnesses for all members of an accumulator, updated lazily on changes.
+//!
+//! Members are stored in the leaves of a complete binary tree. Adding or deleting an element
+//! changes the witnesses of all other members, that is the leaves of the siblings of the path to
+//! the element. Instead of updating all of them, the update is recorded in these `O(log n)`
+//! siblings, and only applied to a witness when it is requested, pushing the pending updates
+//! down the path to its leaf.
+//!
+//! The witnesses are not kept fresh eagerly, as every change alters all of them, which costs an
+//! exponentiation per member. Requesting a witness instead costs an exponentiation per change
+//! since it was last requested, and [WitnessStore::witnesses] brings all of them up to date.
+
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+
+use crate::accumulator::GenericAccumulator;
+use crate::math::{backend, shamir_trick};
+use crate::traits::*;
+
+/// An update of the accumulator, which is pending for all members below a node.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Update {
+    /// The given elements were added, so witnesses are raised to their product.
+    Add(BigUint),
+    /// The given element was deleted, with the given witness, so witnesses are combined with it
+    /// using the shamir trick.
+    Del(BigUint, BigUint),
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Node {
+    /// Number of members below this node.
+    count: usize,
+    /// Updates to apply to all members below this node, in order.
+    updates: Vec<Update>,
+    /// The member and its witness, for occupied leaves.
+    member: Option<(BigUint, BigUint)>,
+}
+
+impl Node {
+    fn push(&mut self, update: Update) {
+        if self.count == 0 {
+            return;
+        }
+
+        // consecutive additions can be merged into a single exponentiation
+        if let (Some(Update::Add(y)), Update::Add(x)) = (self.updates.last_mut(), &update) {
+            *y *= x;
+            return;
+        }
+
+        self.updates.push(update);
+    }
+}
+
+/// An accumulator, with any [AccumulatedSet], together with the lazily updated membership
+/// witnesses of all its members.
+///
+/// Adding or deleting an element costs `O(log n)` besides the update of the accumulator, while
+/// requesting a witness applies the updates since it was last requested.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct WitnessStore<S: AccumulatedSet = BigUint> {
+    acc: GenericAccumulator<S>,
+    /// Number of leaves, a power of two.
+    capacity: usize,
+    /// The tree, in heap order, `nodes[1]` is the root and `nodes[capacity + i]` the `i`-th leaf.
+    nodes: Vec<Node>,
+    /// The leaf index of each member.
+    slots: HashMap<BigUint, usize>,
+    /// Unoccupied leaf indices.
+    free: Vec<usize>,
+}
+
+impl<S: AccumulatedSet> WitnessStore<S> {
+    /// Creates a store for the accumulator `acc`, which must have accumulated exactly `members`.
+    pub fn new(acc: GenericAccumulator<S>, members: &[BigUint]) -> Self {
+        let capacity = members.len().next_power_of_two();
+
+        let mut store = WitnessStore {
+            acc,
+            capacity,
+            nodes: vec![Node::default(); 2 * capacity],
+            slots: HashMap::with_capacity(members.len()),
+            free: (members.len()..capacity).rev().collect(),
+        };
+
+        let witnesses = if members.is_empty() {
+            Vec::new()
+        } else {
+            store.acc.create_all_mem_wit(members)
+        };
+
+        for (i, (x, w)) in members.iter().zip(witnesses).enumerate() {
+            debug_assert!(store.acc.ver_mem(&w, x), "{} was not a member", x);
+
+            store.nodes[capacity + i].member = Some((x.clone(), w));
+            store.slots.insert(x.clone(), i);
+            for v in path(capacity + i) {
+                store.nodes[v].count += 1;
+            }
+        }
+
+        store
+    }
+
+    /// Returns the accumulator.
+    pub fn accumulator(&self) -> &GenericAccumulator<S> {
+        &self.acc
+    }
+
+    /// Returns the number of members.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Returns `true` if `x` is a member.
+    pub fn contains(&self, x: &BigUint) -> bool {
+        self.slots.contains_key(x)
+    }
+
+    /// Returns all members, in no particular order.
+    pub fn members(&self) -> impl Iterator<Item = &BigUint> {
+        self.slots.keys()
+    }
+
+    /// Adds `x`, which must not be a member yet, to the accumulator.
+    pub fn add(&mut self, x: &BigUint) {
+        debug_assert!(!self.contains(x), "{} is already a member", x);
+
+        if self.free.is_empty() {
+            self.grow();
+        }
+        let slot = self.free.pop().expect("grown");
+        let leaf = self.capacity + slot;
+
+        // the witness of x is the state before adding it
+        self.push_down(leaf);
+        let w_x = self.acc.state().clone();
+        self.acc.add(x);
+
+        self.update_siblings(leaf, Update::Add(x.clone()));
+        self.nodes[leaf].member = Some((x.clone(), w_x));
+        for v in path(leaf) {
+            self.nodes[v].count += 1;
+        }
+        self.slots.insert(x.clone(), slot);
+    }
+
+    /// Deletes `x` from the accumulator, using its up to date witness as the new state,
+    /// see [BatchedAccumulator::del_w_mem].
+    /// Returns `None` if `x` is not a member.
+    pub fn del(&mut self, x: &BigUint) -> Option<()> {
+        let w_x = self.witness(x)?;
+        let slot = self.slots.remove(x)?;
+        let leaf = self.capacity + slot;
+
+        self.acc.del_w_mem(&w_x, x).expect("invalid witness");
+
+        self.update_siblings(leaf, Update::Del(x.clone(), w_x));
+        self.nodes[leaf].member = None;
+        for v in path(leaf) {
+            self.nodes[v].count -= 1;
+        }
+        self.free.push(slot);
+
+        Some(())
+    }
+
+    /// Returns the membership witness of `x`, applying all pending updates.
+    /// Returns `None` if `x` is not a member.
+    pub fn witness(&mut self, x: &BigUint) -> Option<BigUint> {
+        let leaf = self.capacity + *self.slots.get(x)?;
+        self.push_down(leaf);
+
+        self.nodes[leaf].member.as_ref().map(|(_, w)| w.clone())
+    }
+
+    /// Returns the membership witnesses of all members, in no particular order.
+    pub fn witnesses(&mut self) -> Vec<(BigUint, BigUint)> {
+        let members = self.members().cloned().collect::<Vec<_>>();
+
+        members
+            .into_iter()
+            .map(|x| {
+                let w = self.witness(&x).expect("member");
+                (x, w)
+            })
+            .collect()
+    }
+
+    /// Records `update` for all members, except the one at `leaf`.
+    /// The path to `leaf` must not have any pending updates.
+    fn update_siblings(&mut self, leaf: usize, update: Update) {
+        let mut v = leaf;
+        while v > 1 {
+            self.nodes[v ^ 1].push(update.clone());
+            v /= 2;
+        }
+    }
+
+    /// Moves all pending updates on the path from the root down to `leaf`, and applies them there.
+    fn push_down(&mut self, leaf: usize) {
+        for v in path(leaf).into_iter().rev() {
+            if v >= self.capacity {
+                self.apply(v);
+                continue;
+            }
+
+            for update in std::mem::take(&mut self.nodes[v].updates) {
+                self.nodes[2 * v].push(update.clone());
+                self.nodes[2 * v + 1].push(update);
+            }
+        }
+    }
+
+    /// Applies the pending updates to the witness at `leaf`.
+    fn apply(&mut self, leaf: usize) {
+        let n = self.acc.modulus().clone();
+        let node = &mut self.nodes[leaf];
+        let updates = std::mem::take(&mut node.updates);
+
+        if let Some((x, w)) = node.member.as_mut() {
+            for update in updates {
+                *w = match update {
+                    Update::Add(y) => backend::modpow(w, &y, &n),
+                    Update::Del(y, w_y) => shamir_trick(w, &w_y, x, &y, &n).expect("invalid state"),
+                };
+            }
+        }
+    }
+
+    /// Doubles the capacity, moving the current tree into the left half of the new one.
+    fn grow(&mut self) {
+        let capacity = std::cmp::max(1, 2 * self.capacity);
+        let mut nodes = vec![Node::default(); 2 * capacity];
+
+        for (v, node) in self.nodes.drain(..).enumerate().skip(1) {
+            // the node at depth d and position p moves to depth d + 1
+            let depth = (usize::BITS - 1 - v.leading_zeros()) as usize;
+            nodes[v + (1 << depth)] = node;
+        }
+        nodes[1].count = nodes[2].count;
+
+        self.free.extend((self.capacity..capacity).rev());
+        self.capacity = capacity;
+        self.nodes = nodes;
+    }
+}
+
+/// Returns the indices of the nodes from `leaf` up to the root.
+fn path(leaf: usize) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut v = leaf;
+    while v >= 1 {
+        path.push(v);
+        v /= 2;
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::accumulator::{Accumulator, ChunkedAccumulator};
+    use crate::group::RSAGroup;
+    use num_bigint::RandPrime;
+    use rand::{thread_rng, Rng};
+
+    #[test]
+    fn test_witness_store() {
+        let mut rng = thread_rng();
+
+        for _ in 0..3 {
+            test_witness_store_with(Accumulator::setup::<RSAGroup, _>(&mut rng, 256));
+        }
+    }
+
+    #[test]
+    fn test_witness_store_chunked() {
+        let mut rng = thread_rng();
+
+        test_witness_store_with(ChunkedAccumulator::setup::<RSAGroup, _>(&mut rng, 256));
+    }
+
+    fn test_witness_store_with<S: AccumulatedSet>(mut acc: GenericAccumulator<S>) {
+        let mut rng = thread_rng();
+
+        let mut members = (0..5).map(|_| rng.gen_prime(64)).collect::<Vec<_>>();
+        acc.batch_add(&members);
+
+        let mut store = WitnessStore::new(acc, &members);
+
+        for _ in 0..30 {
+            if members.is_empty() || rng.gen_bool(0.6) {
+                let x = rng.gen_prime(64);
+                store.add(&x);
+                members.push(x);
+            } else {
+                let x = members.swap_remove(rng.gen_range(0..members.len()));
+                // the witness of x becomes the new state, see del_w_mem
+                let w_x = store.witness(&x).unwrap();
+                assert_eq!(store.del(&x), Some(()));
+                assert_eq!(store.accumulator().state(), &w_x);
+                assert_eq!(store.del(&x), None);
+                assert_eq!(store.witness(&x), None);
+            }
+
+            // check a few witnesses, leaving updates pending for the others
+            let acc = store.accumulator().clone();
+            for x in members.iter().take(3) {
+                let w = store.witness(x).unwrap();
+                assert_eq!(w, acc.mem_wit_create(x));
+            }
+        }
+
+        assert_eq!(store.len(), members.len());
+
+        let acc = store.accumulator().clone();
+        for (x, w) in store.witnesses() {
+            assert!(members.contains(&x));
+            assert!(acc.ver_mem(&w, &x));
+            assert_eq!(w, acc.mem_wit_create(&x));
+        }
+    }
+}
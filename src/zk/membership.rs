@@ -0,0 +1,193 @@
+use num_bigint::{BigInt, BigUint};
+use rand::{CryptoRng, Rng};
+
+use super::{element_offset, element_response_bounded, is_element, ELEMENT_BITS};
+use crate::accumulator::GenericAccumulator;
+use crate::commitment::{
+    blinding, challenge, response, secret_multi_modpow, CommitmentKey, STAT_SECURITY_BITS,
+};
+use crate::math::multi_modpow_int;
+use crate::traits::{AccumulatedSet, StaticAccumulator};
+
+/// Zero-knowledge proof that the value committed to in `C_x = g^x h^r` is accumulated in `A`.
+///
+/// The witness is committed to as `C_w = w h^{r_w}`, `C_r = g^{r_w} h^{r_2}`, and the proof
+/// shows knowledge of `(x, r, r_w, r_2, d = x r_w, d' = x r_2)` with
+/// `C_x = g^x h^r`, `C_r = g^{r_w} h^{r_2}`, `1 = C_r^x g^-d h^-d'` and `A = C_w^x h^-d`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipProof {
+    pub c_w: BigUint,
+    pub c_r: BigUint,
+    pub challenge: BigUint,
+    pub z_x: BigInt,
+    pub z_r: BigInt,
+    pub z_r_w: BigInt,
+    pub z_r_2: BigInt,
+    pub z_d: BigInt,
+    pub z_d_2: BigInt,
+}
+
+/// Proves that the value `x` committed to with randomness `r`, see [CommitmentKey::commit], is
+/// accumulated in `acc`, given its membership witness `w`.
+pub fn prove_membership<S: AccumulatedSet, R: CryptoRng + Rng>(
+    rng: &mut R,
+    ck: &CommitmentKey,
+    acc: &GenericAccumulator<S>,
+    x: &BigUint,
+    r: &BigUint,
+    w: &BigUint,
+) -> MembershipProof {
+    debug_assert!(acc.ver_mem(w, x), "invalid input");
+    debug_assert!(is_element(x), "invalid input");
+    debug_assert!(ck.n() == acc.modulus(), "invalid input");
+
+    let (g, h, n) = (ck.g(), ck.h(), ck.n());
+    let n_bits = n.bits() + STAT_SECURITY_BITS;
+
+    let x = BigInt::from(x.clone());
+    let c_x = ck.commit_with(&x, r);
+    let r = BigInt::from(r.clone());
+    let r_w = BigInt::from(ck.randomness(rng));
+    let r_2 = BigInt::from(ck.randomness(rng));
+    let d = &x * &r_w;
+    let d_2 = &x * &r_2;
+
+    // C_w <- w h^{r_w}, C_r <- g^{r_w} h^{r_2}
+    let c_w = secret_multi_modpow(&[(w, &BigInt::from(1)), (h, &r_w)], n).expect("invalid state");
+    let c_r = secret_multi_modpow(&[(g, &r_w), (h, &r_2)], n).expect("invalid state");
+
+    // x = B + x', only x' < 2^ELEMENT_BITS needs to be blinded
+    let k_x = blinding(rng, ELEMENT_BITS);
+    let k_r = blinding(rng, n_bits);
+    let k_r_w = blinding(rng, n_bits);
+    let k_r_2 = blinding(rng, n_bits);
+    let k_d = blinding(rng, element_offset().bits() + n_bits);
+    let k_d_2 = blinding(rng, element_offset().bits() + n_bits);
+
+    let t_1 = secret_multi_modpow(&[(g, &k_x), (h, &k_r)], n).expect("invalid state");
+    let t_2 = secret_multi_modpow(&[(g, &k_r_w), (h, &k_r_2)], n).expect("invalid state");
+    let t_3 =
+        secret_multi_modpow(&[(&c_r, &k_x), (g, &-&k_d), (h, &-&k_d_2)], n).expect("invalid state");
+    let t_4 = secret_multi_modpow(&[(&c_w, &k_x), (h, &-&k_d)], n).expect("invalid state");
+
+    let c = challenge(
+        ck.transcript()
+            .append(acc.state())
+            .append(&c_x)
+            .append(&c_w)
+            .append(&c_r)
+            .append(&t_1)
+            .append(&t_2)
+            .append(&t_3)
+            .append(&t_4),
+    );
+
+    MembershipProof {
+        z_x: response(&k_x, &c, &x),
+        z_r: response(&k_r, &c, &r),
+        z_r_w: response(&k_r_w, &c, &r_w),
+        z_r_2: response(&k_r_2, &c, &r_2),
+        z_d: response(&k_d, &c, &d),
+        z_d_2: response(&k_d_2, &c, &d_2),
+        c_w,
+        c_r,
+        challenge: c,
+    }
+}
+
+/// Verifies that the value committed to in `c_x` is accumulated in `acc`.
+pub fn verify_membership<S: AccumulatedSet>(
+    ck: &CommitmentKey,
+    acc: &GenericAccumulator<S>,
+    c_x: &BigUint,
+    pi: &MembershipProof,
+) -> bool {
+    let MembershipProof {
+        c_w,
+        c_r,
+        challenge: c,
+        z_x,
+        z_r,
+        z_r_w,
+        z_r_2,
+        z_d,
+        z_d_2,
+    } = pi;
+
+    if !element_response_bounded(z_x, c) {
+        return false;
+    }
+
+    if ck.n() != acc.modulus() {
+        return false;
+    }
+
+    let (g, h, n) = (ck.g(), ck.h(), ck.n());
+    let a = acc.state();
+    let c_int = BigInt::from(c.clone());
+
+    let terms = [
+        // t_1 = C_x^c g^{z_x} h^{z_r}
+        [(c_x, &c_int), (g, z_x), (h, z_r)],
+        // t_2 = C_r^c g^{z_r_w} h^{z_r_2}
+        [(c_r, &c_int), (g, z_r_w), (h, z_r_2)],
+        // t_3 = C_r^{z_x} g^{-z_d} h^{-z_d_2}
+        [(c_r, z_x), (g, &-z_d), (h, &-z_d_2)],
+        // t_4 = A^c C_w^{z_x} h^{-z_d}
+        [(a, &c_int), (c_w, z_x), (h, &-z_d)],
+    ];
+
+    let mut transcript = ck.transcript();
+    transcript.append(a).append(c_x).append(c_w).append(c_r);
+
+    for t in &terms {
+        match multi_modpow_int(t, n) {
+            Some(t) => transcript.append(&t),
+            None => return false,
+        };
+    }
+
+    &challenge(&transcript) == c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::group::RSAGroup;
+    use crate::zk::hash_element;
+    use crate::Accumulator;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_membership() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let mut acc = Accumulator::setup::<RSAGroup, _>(rng, 256);
+
+        let xs = (0..5u8).map(|i| hash_element(&[i])).collect::<Vec<_>>();
+        for x in &xs {
+            acc.add(x);
+        }
+
+        let ck = CommitmentKey::new(acc.generator(), acc.modulus());
+
+        for x in &xs {
+            let w = acc.mem_wit_create(x);
+            let (c_x, r) = ck.commit(rng, x.clone());
+
+            let pi = prove_membership(rng, &ck, &acc, x, &r, &w);
+            assert!(verify_membership(&ck, &acc, &c_x, &pi));
+
+            // other commitment
+            let (c_y, _) = ck.commit(rng, xs[0].clone());
+            assert!(!verify_membership(&ck, &acc, &c_y, &pi));
+
+            // tampered response
+            let mut bad = pi.clone();
+            bad.z_x += 1;
+            assert!(!verify_membership(&ck, &acc, &c_x, &bad));
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Zero-knowledge proofs about accumulated elements, which are only given as commitments.
+//!
+//! Elements are committed to using a [crate::commitment::CommitmentKey] for the group of the accumulator, and the
+//! proofs are Camenisch-Lysyanskaya style sigma protocols, see [crate::commitment].
+//!
+//! Knowledge of `w` with `w^x = A` is trivial for `x = 1`, so the proofs also bound the committed
+//! value. Only elements in `[B, B + 2^ELEMENT_BITS)`, with `B = ` [element_offset], can be proven
+//! about, such as those created by [hash_element].
+
+use blake2::{Blake2b512, Digest};
+use num_bigint::prime::probably_prime;
+use num_bigint::{BigInt, BigUint};
+use num_traits::One;
+
+use crate::commitment::{CHALLENGE_BITS, STAT_SECURITY_BITS};
+
+mod membership;
+mod non_membership;
+
+pub use self::membership::*;
+pub use self::non_membership::*;
+
+/// Size of the elements, above the [element_offset], in bits.
+pub const ELEMENT_BITS: usize = 128;
+
+/// Returns `B = 2^(ELEMENT_BITS + CHALLENGE_BITS + STAT_SECURITY_BITS + 2)`, the smallest element.
+///
+/// The proofs only bound the committed value to `B ± 2^(ELEMENT_BITS + CHALLENGE_BITS +
+/// STAT_SECURITY_BITS + 1)`, which is large enough to exclude `x = ±1`.
+pub fn element_offset() -> BigUint {
+    BigUint::one() << (ELEMENT_BITS + CHALLENGE_BITS + STAT_SECURITY_BITS + 2)
+}
+
+/// Returns `true` if `x` is in `[B, B + 2^ELEMENT_BITS)`.
+pub fn is_element(x: &BigUint) -> bool {
+    let offset = element_offset();
+    x >= &offset && (x - &offset).bits() <= ELEMENT_BITS
+}
+
+/// Hashes the input to a prime element in `[B, B + 2^ELEMENT_BITS)`.
+pub fn hash_element(input: &[u8]) -> BigUint {
+    let offset = element_offset();
+    let mut y = Blake2b512::digest(input)[..ELEMENT_BITS / 8].to_vec();
+
+    loop {
+        let x = &offset + BigUint::from_bytes_be(&y);
+        if probably_prime(&x, 20) {
+            return x;
+        }
+        y = Blake2b512::digest(&y)[..ELEMENT_BITS / 8].to_vec();
+    }
+}
+
+/// Checks the response `z_x = k_x - c x` for an element `x`, where `k_x` blinds `ELEMENT_BITS` bits.
+///
+/// `z_x + c B = k_x - c (x - B)` is bounded, so the committed value is close to `B`.
+fn element_response_bounded(z_x: &BigInt, c: &BigUint) -> bool {
+    let shifted = z_x + BigInt::from(c * element_offset());
+    shifted.bits() <= ELEMENT_BITS + CHALLENGE_BITS + STAT_SECURITY_BITS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_element() {
+        for i in 0..10u8 {
+            let x = hash_element(&[i]);
+            assert!(is_element(&x));
+            assert!(probably_prime(&x, 20));
+        }
+
+        assert!(!is_element(&BigUint::one()));
+        assert!(!is_element(&(element_offset() << 1)));
+    }
+}
@@ -0,0 +1,209 @@
+use num_bigint::{BigInt, BigUint};
+use rand::{CryptoRng, Rng};
+
+use super::{element_offset, element_response_bounded, is_element, ELEMENT_BITS};
+use crate::accumulator::GenericAccumulator;
+use crate::commitment::{
+    blinding, challenge, response, secret_multi_modpow, CommitmentKey, STAT_SECURITY_BITS,
+};
+use crate::math::multi_modpow_int;
+use crate::traits::{AccumulatedSet, StaticAccumulator, UniversalAccumulator};
+
+/// Zero-knowledge proof that the value committed to in `C_x = g^x h^r` is not accumulated in `A`.
+///
+/// Based on the non-membership witness `(d, b)`, with `d^x A^b = g_A`. `d` is committed to as
+/// `C_d = d h^{r_d}`, `C_r = g^{r_d} h^{r_2}`, and the proof shows knowledge of
+/// `(x, r, b, r_d, r_2, e = x r_d, e' = x r_2)` with `C_x = g^x h^r`, `C_r = g^{r_d} h^{r_2}`,
+/// `1 = C_r^x g^-e h^-e'` and `g_A = C_d^x A^b h^-e`, where `g_A` is the generator of the
+/// accumulator.
+/// Like NI-PoKE2 for `A^b`, knowledge of `b` is shown using an integer response.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonMembershipProof {
+    pub c_d: BigUint,
+    pub c_r: BigUint,
+    pub challenge: BigUint,
+    pub z_x: BigInt,
+    pub z_r: BigInt,
+    pub z_b: BigInt,
+    pub z_r_d: BigInt,
+    pub z_r_2: BigInt,
+    pub z_e: BigInt,
+    pub z_e_2: BigInt,
+}
+
+/// Proves that the value `x` committed to with randomness `r`, see [CommitmentKey::commit], is not
+/// accumulated in `acc`, given its non-membership witness `(d, b)`.
+pub fn prove_non_membership<S: AccumulatedSet, R: CryptoRng + Rng>(
+    rng: &mut R,
+    ck: &CommitmentKey,
+    acc: &GenericAccumulator<S>,
+    x: &BigUint,
+    r: &BigUint,
+    w: &(BigUint, BigInt),
+) -> NonMembershipProof {
+    debug_assert!(acc.ver_non_mem(w, x), "invalid input");
+    debug_assert!(is_element(x), "invalid input");
+    debug_assert!(ck.n() == acc.modulus(), "invalid input");
+
+    let (d, b) = w;
+    let (g, h, n) = (ck.g(), ck.h(), ck.n());
+    let n_bits = n.bits() + STAT_SECURITY_BITS;
+    let x_bits = element_offset().bits() + 1;
+
+    let x = BigInt::from(x.clone());
+    let c_x = ck.commit_with(&x, r);
+    let r = BigInt::from(r.clone());
+    let r_d = BigInt::from(ck.randomness(rng));
+    let r_2 = BigInt::from(ck.randomness(rng));
+    let e = &x * &r_d;
+    let e_2 = &x * &r_2;
+
+    // C_d <- d h^{r_d}, C_r <- g^{r_d} h^{r_2}
+    let c_d = secret_multi_modpow(&[(d, &BigInt::from(1)), (h, &r_d)], n).expect("invalid state");
+    let c_r = secret_multi_modpow(&[(g, &r_d), (h, &r_2)], n).expect("invalid state");
+
+    // x = B + x', only x' < 2^ELEMENT_BITS needs to be blinded, and |b| < x
+    let k_x = blinding(rng, ELEMENT_BITS);
+    let k_r = blinding(rng, n_bits);
+    let k_b = blinding(rng, x_bits);
+    let k_r_d = blinding(rng, n_bits);
+    let k_r_2 = blinding(rng, n_bits);
+    let k_e = blinding(rng, x_bits + n_bits);
+    let k_e_2 = blinding(rng, x_bits + n_bits);
+
+    let t_1 = secret_multi_modpow(&[(g, &k_x), (h, &k_r)], n).expect("invalid state");
+    let t_2 = secret_multi_modpow(&[(g, &k_r_d), (h, &k_r_2)], n).expect("invalid state");
+    let t_3 =
+        secret_multi_modpow(&[(&c_r, &k_x), (g, &-&k_e), (h, &-&k_e_2)], n).expect("invalid state");
+    let t_4 = secret_multi_modpow(&[(&c_d, &k_x), (acc.state(), &k_b), (h, &-&k_e)], n)
+        .expect("invalid state");
+
+    let c = challenge(
+        ck.transcript()
+            .append(acc.state())
+            .append(&c_x)
+            .append(&c_d)
+            .append(&c_r)
+            .append(&t_1)
+            .append(&t_2)
+            .append(&t_3)
+            .append(&t_4),
+    );
+
+    NonMembershipProof {
+        z_x: response(&k_x, &c, &x),
+        z_r: response(&k_r, &c, &r),
+        z_b: response(&k_b, &c, b),
+        z_r_d: response(&k_r_d, &c, &r_d),
+        z_r_2: response(&k_r_2, &c, &r_2),
+        z_e: response(&k_e, &c, &e),
+        z_e_2: response(&k_e_2, &c, &e_2),
+        c_d,
+        c_r,
+        challenge: c,
+    }
+}
+
+/// Verifies that the value committed to in `c_x` is not accumulated in `acc`.
+pub fn verify_non_membership<S: AccumulatedSet>(
+    ck: &CommitmentKey,
+    acc: &GenericAccumulator<S>,
+    c_x: &BigUint,
+    pi: &NonMembershipProof,
+) -> bool {
+    let NonMembershipProof {
+        c_d,
+        c_r,
+        challenge: c,
+        z_x,
+        z_r,
+        z_b,
+        z_r_d,
+        z_r_2,
+        z_e,
+        z_e_2,
+    } = pi;
+
+    if !element_response_bounded(z_x, c) {
+        return false;
+    }
+
+    if ck.n() != acc.modulus() {
+        return false;
+    }
+
+    let (g, h, n) = (ck.g(), ck.h(), ck.n());
+    let a = acc.state();
+    let c_int = BigInt::from(c.clone());
+
+    let terms: [&[(&BigUint, &BigInt)]; 4] = [
+        // t_1 = C_x^c g^{z_x} h^{z_r}
+        &[(c_x, &c_int), (g, z_x), (h, z_r)],
+        // t_2 = C_r^c g^{z_r_d} h^{z_r_2}
+        &[(c_r, &c_int), (g, z_r_d), (h, z_r_2)],
+        // t_3 = C_r^{z_x} g^{-z_e} h^{-z_e_2}
+        &[(c_r, z_x), (g, &-z_e), (h, &-z_e_2)],
+        // t_4 = g_A^c C_d^{z_x} A^{z_b} h^{-z_e}
+        &[(acc.generator(), &c_int), (c_d, z_x), (a, z_b), (h, &-z_e)],
+    ];
+
+    let mut transcript = ck.transcript();
+    transcript.append(a).append(c_x).append(c_d).append(c_r);
+
+    for t in &terms {
+        match multi_modpow_int(t, n) {
+            Some(t) => transcript.append(&t),
+            None => return false,
+        };
+    }
+
+    &challenge(&transcript) == c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::group::RSAGroup;
+    use crate::zk::hash_element;
+    use crate::Accumulator;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    #[test]
+    fn test_non_membership() {
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let mut acc = Accumulator::setup::<RSAGroup, _>(rng, 256);
+
+        let xs = (0..5u8).map(|i| hash_element(&[i])).collect::<Vec<_>>();
+        for x in &xs {
+            acc.add(x);
+        }
+
+        let ck = CommitmentKey::new(acc.generator(), acc.modulus());
+
+        for i in 5..8u8 {
+            let y = hash_element(&[i]);
+            let w = acc.non_mem_wit_create(&y).unwrap();
+            let (c_y, r) = ck.commit(rng, y.clone());
+
+            let pi = prove_non_membership(rng, &ck, &acc, &y, &r, &w);
+            assert!(verify_non_membership(&ck, &acc, &c_y, &pi));
+
+            // commitment to a member
+            let (c_x, _) = ck.commit(rng, xs[0].clone());
+            assert!(!verify_non_membership(&ck, &acc, &c_x, &pi));
+
+            // tampered response
+            let mut bad = pi.clone();
+            bad.z_b += 1;
+            assert!(!verify_non_membership(&ck, &acc, &c_y, &bad));
+
+            // y is added afterwards
+            let mut acc_y = acc.clone();
+            acc_y.add(&y);
+            assert!(!verify_non_membership(&ck, &acc_y, &c_y, &pi));
+        }
+    }
+}